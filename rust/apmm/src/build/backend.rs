@@ -0,0 +1,144 @@
+//! 外部构建后端协议
+//!
+//! `[build.system] build-backend` 为 "apmm" 时使用内置打包器，其他值按以下规则解析：
+//! - 含路径分隔符的值视为相对项目根目录的可执行文件或脚本
+//! - 否则在 PATH 中查找 `apmm-backend-<name>`
+//!
+//! APMM 先按 [build.module] / 变体 / [build.native] 规则完成暂存，然后以 `build`
+//! 为参数启动后端，通过 stdin 传入 JSON 描述（见 [`BackendRequest`]）。
+//! 后端在 stdout 输出 `{"artifacts": ["<path>", ...]}`，路径相对项目根目录或为绝对路径；
+//! stderr 直接显示给用户，非零退出码视为构建失败。
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cmds::ApmmConfig;
+use super::steps;
+
+/// 内置后端名称
+pub const BUILTIN_BACKEND: &str = "apmm";
+/// 协议版本
+const PROTOCOL_VERSION: u32 = 1;
+
+/// 传给后端的项目元数据
+#[derive(Debug, Serialize)]
+struct BackendProject<'a> {
+    id: &'a str,
+    name: &'a str,
+    version: &'a str,
+    #[serde(rename = "versionCode")]
+    version_code: i64,
+    author: &'a str,
+    description: &'a str,
+    license: &'a str,
+}
+
+/// 通过 stdin 传给后端的 JSON
+#[derive(Debug, Serialize)]
+struct BackendRequest<'a> {
+    protocol: u32,
+    project: BackendProject<'a>,
+    project_dir: String,
+    staging_dir: String,
+    dist_dir: String,
+    variant: Option<&'a str>,
+    /// 暂存目录中的文件（相对路径）
+    files: Vec<String>,
+}
+
+/// 后端在 stdout 返回的 JSON
+#[derive(Debug, Deserialize)]
+struct BackendResponse {
+    artifacts: Vec<String>,
+}
+
+/// 调用外部后端打包暂存目录，返回其生成的产物
+pub fn run_backend(
+    backend: &str,
+    project_dir: &Path,
+    config: &ApmmConfig,
+    staging_dir: &Path,
+    dist_dir: &Path,
+    variant: Option<&str>,
+    files: Vec<String>,
+) -> Result<Vec<PathBuf>, String> {
+    let program = resolve_backend(backend, project_dir);
+    println!("🔌 Packaging with build backend '{}' ({})...", backend, program.display());
+
+    let request = BackendRequest {
+        protocol: PROTOCOL_VERSION,
+        project: BackendProject {
+            id: &config.id,
+            name: &config.name,
+            version: &config.version,
+            version_code: config.version_code,
+            author: &config.author,
+            description: &config.description,
+            license: &config.license,
+        },
+        project_dir: project_dir.to_string_lossy().to_string(),
+        staging_dir: staging_dir.to_string_lossy().to_string(),
+        dist_dir: dist_dir.to_string_lossy().to_string(),
+        variant,
+        files,
+    };
+    let payload = serde_json::to_vec(&request)
+        .map_err(|e| format!("Failed to serialize backend request: {}", e))?;
+
+    let mut child = Command::new(&program)
+        .arg("build")
+        .current_dir(project_dir)
+        .envs(steps::step_env("build", project_dir, config))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("Failed to start build backend '{}' ({}): {}", backend, program.display(), e))?;
+
+    // 在单独的线程中写入请求，同时读取 stdout，避免双方的管道缓冲区都写满时互相等待
+    let writer = child.stdin.take().map(|mut stdin| thread::spawn(move || stdin.write_all(&payload)));
+
+    let output = child.wait_with_output()
+        .map_err(|e| format!("Build backend '{}' did not finish: {}", backend, e))?;
+    let written = writer.map_or(Ok(()), |writer| writer.join().unwrap_or(Ok(())));
+    if !output.status.success() {
+        let reason = match output.status.code() {
+            Some(code) => format!("exit code {}", code),
+            None => "terminated by signal".to_string(),
+        };
+        return Err(format!("Build backend '{}' failed with {}", backend, reason));
+    }
+
+    // 后端没有读完请求就正常退出时管道会被关闭，此时不算错误
+    if let Err(e) = written
+        && e.kind() != io::ErrorKind::BrokenPipe
+    {
+        return Err(format!("Failed to send request to build backend '{}': {}", backend, e));
+    }
+
+    let response: BackendResponse = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Build backend '{}' returned invalid JSON: {}", backend, e))?;
+
+    let mut artifacts = Vec::new();
+    for artifact in response.artifacts {
+        let path = project_dir.join(&artifact);
+        if !path.exists() {
+            return Err(format!("Build backend '{}' reported missing artifact: {}", backend, artifact));
+        }
+        artifacts.push(path);
+    }
+    Ok(artifacts)
+}
+
+/// 解析后端可执行文件
+fn resolve_backend(backend: &str, project_dir: &Path) -> PathBuf {
+    if backend.contains('/') || backend.contains('\\') {
+        project_dir.join(backend)
+    } else {
+        PathBuf::from(format!("apmm-backend-{}", backend))
+    }
+}
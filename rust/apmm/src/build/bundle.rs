@@ -0,0 +1,251 @@
+//! 模块脚本打包：把 source 的辅助脚本内联到单个脚本中
+//!
+//! 暂存目录顶层的 `*.sh`（customize.sh、service.sh 等）中以下两种写法会被展开：
+//! - `. ./lib/foo.sh` / `source lib/foo.sh` / `. "$MODDIR/lib/foo.sh"`：
+//!   文件在模块中存在时内联，否则保持原样（例如运行时才存在的文件）
+//! - `# apmm:include lib/foo.sh`：文件必须存在，否则构建失败
+//!
+//! 路径都相对模块根目录解析，被包含的文件也会递归展开。每个文件只内联一次，
+//! 循环包含会报错；内联内容前后写入 `# apmm:line <文件>:<行号>` 注释，
+//! 便于把设备上的报错行号对应回源文件。
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path};
+
+/// 显式包含指令
+const INCLUDE_DIRECTIVE: &str = "# apmm:include ";
+/// source 路径中表示模块根目录的前缀
+const MODULE_ROOT_PREFIXES: &[&str] = &["$MODDIR/", "${MODDIR}/", "$MODPATH/", "${MODPATH}/", "./"];
+
+/// 展开暂存目录中顶层脚本的包含，返回被修改的脚本数
+pub fn bundle_staged(staging_dir: &Path, rel_paths: &[&str]) -> Result<usize, String> {
+    let mut bundled = 0;
+
+    for rel_path in rel_paths {
+        if rel_path.contains('/') || !rel_path.ends_with(".sh") {
+            continue;
+        }
+        let path = staging_dir.join(rel_path);
+        let content = read_script(&path)?;
+
+        let mut bundler = Bundler { staging_dir, stack: Vec::new(), included: HashSet::new() };
+        let mut output = String::with_capacity(content.len());
+        bundler.expand(rel_path, &content, 1, &mut output)?;
+
+        if !bundler.included.is_empty() {
+            fs::write(&path, output)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            println!("   Bundled {} helper(s) into {}", bundler.included.len(), rel_path);
+            bundled += 1;
+        }
+    }
+
+    Ok(bundled)
+}
+
+struct Bundler<'a> {
+    staging_dir: &'a Path,
+    /// 当前的包含链，用于检测循环
+    stack: Vec<String>,
+    /// 已内联的文件
+    included: HashSet<String>,
+}
+
+impl Bundler<'_> {
+    /// 展开 content 中的包含，first_line 为 content 第一行在源文件中的行号
+    fn expand(&mut self, rel_path: &str, content: &str, first_line: usize, output: &mut String) -> Result<(), String> {
+        self.stack.push(rel_path.to_string());
+
+        for (offset, line) in content.lines().enumerate() {
+            let line_no = first_line + offset;
+            let Some((target, required)) = include_target(line) else {
+                output.push_str(line);
+                output.push('\n');
+                continue;
+            };
+
+            let location = format!("{}:{}", rel_path, line_no);
+            let target_path = self.staging_dir.join(&target);
+            if !target_path.is_file() {
+                if required {
+                    return Err(format!("{}: included file not found in module: {}", location, target));
+                }
+                output.push_str(line);
+                output.push('\n');
+                continue;
+            }
+
+            if self.stack.contains(&target) {
+                let mut chain = self.stack.clone();
+                chain.push(target);
+                return Err(format!("{}: include loop detected: {}", location, chain.join(" -> ")));
+            }
+            if !self.included.insert(target.clone()) {
+                output.push_str(&format!("# apmm:include {} (already inlined)\n", target));
+                continue;
+            }
+
+            let included = read_script(&target_path)?;
+            output.push_str(&format!("# >>> apmm:include {} (from {})\n", target, location));
+            // 被包含文件的 shebang 没有意义，跳过但保持行号对应
+            let (body, first_line) = match included.strip_prefix("#!") {
+                Some(rest) => (rest.split_once('\n').map(|(_, body)| body).unwrap_or(""), 2),
+                None => (included.as_str(), 1),
+            };
+            output.push_str(&format!("# apmm:line {}:{}\n", target, first_line));
+            self.expand(&target, body, first_line, output)?;
+            output.push_str(&format!("# <<< apmm:include {}\n", target));
+            output.push_str(&format!("# apmm:line {}:{}\n", rel_path, line_no + 1));
+        }
+
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+/// 解析一行中的包含目标，返回（模块内相对路径，是否必须存在）
+pub(super) fn include_target(line: &str) -> Option<(String, bool)> {
+    let line = line.trim();
+
+    if let Some(path) = line.strip_prefix(INCLUDE_DIRECTIVE) {
+        return normalize(path.trim().trim_matches(['"', '\''])).map(|p| (p, true));
+    }
+
+    let rest = line.strip_prefix(". ").or_else(|| line.strip_prefix("source "))?;
+    let mut args = rest.split_whitespace();
+    let path = args.next()?.trim_matches(['"', '\'']);
+    // 带参数的 source 无法安全内联
+    if args.next().is_some() {
+        return None;
+    }
+
+    let path = MODULE_ROOT_PREFIXES.iter()
+        .find_map(|prefix| path.strip_prefix(prefix))
+        .unwrap_or(path);
+    if path.starts_with('/') || path.contains('$') || path.contains('`') {
+        return None;
+    }
+    normalize(path).map(|p| (p, false))
+}
+
+/// 规范化为模块内的相对路径，拒绝跳出模块根目录的路径
+fn normalize(path: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {},
+            _ => return None,
+        }
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+fn read_script(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn staging(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let staging_dir = std::env::temp_dir().join(format!("apmm-bundle-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&staging_dir);
+        for (rel_path, content) in files {
+            let path = staging_dir.join(rel_path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        staging_dir
+    }
+
+    fn expand(staging_dir: &Path, rel_path: &str) -> Result<String, String> {
+        let content = read_script(&staging_dir.join(rel_path))?;
+        let mut bundler = Bundler { staging_dir, stack: Vec::new(), included: HashSet::new() };
+        let mut output = String::new();
+        bundler.expand(rel_path, &content, 1, &mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn expands_nested_includes_with_line_markers() {
+        let staging_dir = staging("nested", &[
+            ("customize.sh", "#!/system/bin/sh\n. ./lib/a.sh\necho done\n"),
+            ("lib/a.sh", "#!/system/bin/sh\nsource lib/b.sh\na=1\n"),
+            ("lib/b.sh", "b=1\n"),
+        ]);
+        assert_eq!(expand(&staging_dir, "customize.sh").unwrap(), "\
+#!/system/bin/sh
+# >>> apmm:include lib/a.sh (from customize.sh:2)
+# apmm:line lib/a.sh:2
+# >>> apmm:include lib/b.sh (from lib/a.sh:2)
+# apmm:line lib/b.sh:1
+b=1
+# <<< apmm:include lib/b.sh
+# apmm:line lib/a.sh:3
+a=1
+# <<< apmm:include lib/a.sh
+# apmm:line customize.sh:3
+echo done
+");
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+
+    #[test]
+    fn inlines_each_file_once() {
+        let staging_dir = staging("once", &[
+            ("service.sh", ". \"$MODDIR/lib/util.sh\"\n# apmm:include lib/util.sh\n"),
+            ("lib/util.sh", "util=1\n"),
+        ]);
+        let output = expand(&staging_dir, "service.sh").unwrap();
+        assert_eq!(output.matches("util=1").count(), 1);
+        assert!(output.ends_with("# apmm:include lib/util.sh (already inlined)\n"));
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+
+    #[test]
+    fn reports_include_loops() {
+        let staging_dir = staging("loop", &[
+            ("customize.sh", ". ./lib/a.sh\n"),
+            ("lib/a.sh", ". ./lib/b.sh\n"),
+            ("lib/b.sh", "\n. ./lib/a.sh\n"),
+        ]);
+        assert_eq!(
+            expand(&staging_dir, "customize.sh"),
+            Err("lib/b.sh:2: include loop detected: customize.sh -> lib/a.sh -> lib/b.sh -> lib/a.sh".to_string())
+        );
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+
+    #[test]
+    fn missing_files_fail_only_when_required() {
+        let staging_dir = staging("missing", &[
+            ("customize.sh", ". ./lib/runtime.sh\n"),
+            ("service.sh", "echo start\n# apmm:include lib/gone.sh\n"),
+        ]);
+        assert_eq!(expand(&staging_dir, "customize.sh").unwrap(), ". ./lib/runtime.sh\n");
+        assert_eq!(
+            expand(&staging_dir, "service.sh"),
+            Err("service.sh:2: included file not found in module: lib/gone.sh".to_string())
+        );
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+
+    #[test]
+    fn include_targets() {
+        assert_eq!(include_target(". \"${MODPATH}/lib/x.sh\""), Some(("lib/x.sh".to_string(), false)));
+        assert_eq!(include_target("# apmm:include ./lib/x.sh"), Some(("lib/x.sh".to_string(), true)));
+        assert_eq!(include_target(". ./lib/x.sh arg"), None);
+        assert_eq!(include_target(". /data/adb/x.sh"), None);
+        assert_eq!(include_target(". ../x.sh"), None);
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::package;
+use super::pattern;
+
+/// 构建缓存文件（位于 .apmm/ 下）
+pub const CACHE_FILE: &str = "build-cache.toml";
+
+/// 单个输入文件的记录：大小和修改时间未变时直接复用哈希
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub size: u64,
+    pub mtime: i64,
+    pub sha256: String,
+}
+
+/// 上一次成功执行的构建步骤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub fingerprint: String,
+    pub log: String,
+    /// 步骤新建或修改的文件（相对项目目录）及其 SHA-256
+    #[serde(default)]
+    pub outputs: BTreeMap<String, String>,
+}
+
+/// 上一次打包的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageRecord {
+    pub fingerprint: String,
+    pub artifact: String,
+    pub sha256: String,
+}
+
+/// 增量构建缓存
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuildCache {
+    #[serde(default)]
+    pub files: BTreeMap<String, FileRecord>,
+    #[serde(default)]
+    pub steps: BTreeMap<String, StepRecord>,
+    #[serde(default)]
+    pub packages: BTreeMap<String, PackageRecord>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl BuildCache {
+    /// 加载 .apmm/build-cache.toml，不存在或损坏时返回空缓存
+    pub fn load(project_dir: &Path) -> Self {
+        let path = project_dir.join(".apmm").join(CACHE_FILE);
+        let mut cache: BuildCache = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default();
+        cache.path = path;
+        cache
+    }
+
+    /// 创建空缓存（用于 --force），保存时会覆盖旧缓存
+    pub fn empty(project_dir: &Path) -> Self {
+        BuildCache {
+            path: project_dir.join(".apmm").join(CACHE_FILE),
+            ..Default::default()
+        }
+    }
+
+    /// 保存缓存
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = toml::to_string(self)
+            .map_err(|e| format!("Failed to serialize build cache: {}", e))?;
+        fs::write(&self.path, content)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+
+    /// 获取文件内容哈希，优先复用缓存记录
+    pub fn file_hash(&mut self, key: &str, path: &Path) -> Result<String, String> {
+        let metadata = fs::metadata(path)
+            .map_err(|e| format!("Failed to read metadata of {}: {}", path.display(), e))?;
+        let (size, mtime) = stamp(&metadata);
+
+        if let Some(record) = self.files.get(key)
+            && record.size == size
+            && record.mtime == mtime
+        {
+            return Ok(record.sha256.clone());
+        }
+
+        let sha256 = hash_file(path)?;
+        self.files.insert(key.to_string(), FileRecord { size, mtime, sha256: sha256.clone() });
+        Ok(sha256)
+    }
+
+    /// 计算整个项目（不含 build/、.apmm/、.git/）的内容指纹，同时清理已删除文件的记录
+    ///
+    /// 构建步骤自己生成的文件不计入指纹，否则步骤每次执行后都会让自己失效
+    pub fn project_fingerprint(&mut self, project_dir: &Path) -> Result<String, String> {
+        let files = tracked_files(project_dir)?;
+        let outputs: HashSet<String> = self.steps.values().flat_map(|record| record.outputs.keys().cloned()).collect();
+        let build_prefix = format!("{}/", package::BUILD_DIR);
+        let inputs: Vec<&String> = files.iter()
+            .filter(|rel_path| !rel_path.starts_with(&build_prefix) && !outputs.contains(*rel_path))
+            .collect();
+        let fingerprint = self.paths_fingerprint(project_dir, &inputs)?;
+
+        // 项目外的 extra 文件以绝对路径记录，不在此处清理
+        self.files.retain(|key, _| files.binary_search(key).is_ok() || Path::new(key).is_absolute());
+
+        Ok(fingerprint)
+    }
+
+    /// 计算匹配 patterns 的项目文件和其他步骤生成的文件的内容指纹
+    ///
+    /// build/ 下只有步骤生成的文件算作输入，暂存目录和产物不算；步骤自己生成的文件也不算
+    pub fn inputs_fingerprint(&mut self, project_dir: &Path, key: &str, patterns: &[String]) -> Result<String, String> {
+        let mut files = package::collect_files(project_dir, &[])?;
+        files.extend(self.steps.iter()
+            .filter(|(step, _)| *step != key)
+            .flat_map(|(_, record)| record.outputs.keys().cloned()));
+        files.sort();
+        files.dedup();
+        let inputs: Vec<&String> = files.iter()
+            .filter(|rel_path| pattern::matches_any(patterns, rel_path) && project_dir.join(rel_path).is_file())
+            .collect();
+        self.paths_fingerprint(project_dir, &inputs)
+    }
+
+    fn paths_fingerprint(&mut self, project_dir: &Path, rel_paths: &[&String]) -> Result<String, String> {
+        let mut hasher = Sha256::new();
+        for rel_path in rel_paths {
+            let hash = self.file_hash(rel_path, &project_dir.join(rel_path))?;
+            hasher.update(rel_path.as_bytes());
+            hasher.update([0]);
+            hasher.update(hash.as_bytes());
+            hasher.update([0]);
+        }
+        Ok(hex(&hasher.finalize()))
+    }
+
+    /// 计算一组待打包文件（源路径, 包内路径）的指纹
+    pub fn files_fingerprint(&mut self, project_dir: &Path, files: &[(PathBuf, String)], salt: &str) -> Result<String, String> {
+        let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
+        hasher.update([0]);
+        for (source, rel_path) in files {
+            let key = match source.strip_prefix(project_dir) {
+                Ok(rel) => rel.to_string_lossy().replace('\\', "/"),
+                Err(_) => source.to_string_lossy().to_string(),
+            };
+            let hash = self.file_hash(&key, source)?;
+            hasher.update(rel_path.as_bytes());
+            hasher.update([0]);
+            hasher.update(hash.as_bytes());
+            hasher.update([0]);
+        }
+        Ok(hex(&hasher.finalize()))
+    }
+
+    /// 构建步骤是否与上次成功执行时的指纹一致，且它生成的文件都还在、未被改动
+    ///
+    /// 没有生成任何文件的步骤（例如部署、上传）无法判断是否已经完成，总是重新执行
+    pub fn step_up_to_date(&self, project_dir: &Path, key: &str, fingerprint: &str) -> bool {
+        self.steps.get(key).is_some_and(|record| {
+            record.fingerprint == fingerprint
+                && !record.outputs.is_empty()
+                && record.outputs.iter().all(|(rel_path, sha256)| {
+                    hash_file(&project_dir.join(rel_path)).is_ok_and(|sha| sha == *sha256)
+                })
+        })
+    }
+
+    /// 记录成功执行的构建步骤及其生成的文件
+    pub fn record_step(&mut self, project_dir: &Path, key: &str, fingerprint: String, log: &Path, outputs: Vec<String>) -> Result<(), String> {
+        let outputs = outputs.into_iter()
+            .map(|rel_path| hash_file(&project_dir.join(&rel_path)).map(|sha256| (rel_path, sha256)))
+            .collect::<Result<_, String>>()?;
+        self.steps.insert(key.to_string(), StepRecord {
+            fingerprint,
+            log: log.to_string_lossy().to_string(),
+            outputs,
+        });
+        Ok(())
+    }
+
+    /// 已有产物是否仍然有效（指纹一致且文件未被改动）
+    pub fn package_up_to_date(&self, name: &str, fingerprint: &str) -> bool {
+        match self.packages.get(name) {
+            Some(record) if record.fingerprint == fingerprint => {
+                hash_file(Path::new(&record.artifact)).is_ok_and(|sha| sha == record.sha256)
+            },
+            _ => false,
+        }
+    }
+
+    /// 记录新生成的产物
+    pub fn record_package(&mut self, name: &str, fingerprint: String, artifact: &Path) -> Result<(), String> {
+        let sha256 = hash_file(artifact)?;
+        self.packages.insert(name.to_string(), PackageRecord {
+            fingerprint,
+            artifact: artifact.to_string_lossy().to_string(),
+            sha256,
+        });
+        Ok(())
+    }
+}
+
+/// 项目文件（含 build/ 下的文件）的大小和修改时间，用于找出构建步骤生成的文件
+pub fn snapshot(project_dir: &Path) -> Result<BTreeMap<String, (u64, i64)>, String> {
+    let mut stamps = BTreeMap::new();
+    for rel_path in tracked_files(project_dir)? {
+        if let Ok(metadata) = fs::metadata(project_dir.join(&rel_path)) {
+            stamps.insert(rel_path, stamp(&metadata));
+        }
+    }
+    Ok(stamps)
+}
+
+/// 项目文件和 build/ 下的文件（不含 .apmm/、.git/），按路径排序
+fn tracked_files(project_dir: &Path) -> Result<Vec<String>, String> {
+    let build_dir = project_dir.join(package::BUILD_DIR);
+    let mut rel_paths = package::collect_files(project_dir, &[])?;
+    if build_dir.is_dir() {
+        rel_paths.extend(package::collect_files(&build_dir, &[])?
+            .into_iter()
+            .map(|rel_path| format!("{}/{}", package::BUILD_DIR, rel_path)));
+    }
+    rel_paths.sort();
+    Ok(rel_paths)
+}
+
+/// 文件大小和修改时间（纳秒）
+fn stamp(metadata: &Metadata) -> (u64, i64) {
+    let mtime = metadata.modified().ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0);
+    (metadata.len(), mtime)
+}
+
+/// 计算字符串的 SHA-256
+pub fn hash_str(content: &str) -> String {
+    hash_bytes(content.as_bytes())
+}
+
+/// 计算字节内容的 SHA-256
+pub fn hash_bytes(content: &[u8]) -> String {
+    hex(&Sha256::digest(content))
+}
+
+/// 计算文件的 SHA-256
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .map_err(|e| format!("Failed to hash {}: {}", path.display(), e))?;
+    Ok(hex(&hasher.finalize()))
+}
+
+pub(super) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str) -> PathBuf {
+        let project_dir = std::env::temp_dir().join(format!("apmm-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&project_dir);
+        fs::create_dir_all(project_dir.join("src")).unwrap();
+        fs::create_dir_all(project_dir.join("build")).unwrap();
+        fs::write(project_dir.join("src/main.c"), "int main;").unwrap();
+        fs::write(project_dir.join("README.md"), "readme").unwrap();
+        fs::write(project_dir.join("build/main.o"), "object").unwrap();
+        project_dir
+    }
+
+    fn record(cache: &mut BuildCache, project_dir: &Path, fingerprint: &str, outputs: &[&str]) {
+        let outputs = outputs.iter().map(|output| output.to_string()).collect();
+        cache.record_step(project_dir, "prebuild.cc", fingerprint.to_string(), Path::new("cc.log"), outputs).unwrap();
+    }
+
+    #[test]
+    fn steps_only_depend_on_their_inputs() {
+        let project_dir = project("inputs");
+        let mut cache = BuildCache::empty(&project_dir);
+        let inputs = ["src".to_string()];
+        let fingerprint = cache.inputs_fingerprint(&project_dir, "prebuild.cc", &inputs).unwrap();
+        record(&mut cache, &project_dir, &fingerprint, &["build/main.o"]);
+
+        fs::write(project_dir.join("README.md"), "changed readme").unwrap();
+        let unrelated = cache.inputs_fingerprint(&project_dir, "prebuild.cc", &inputs).unwrap();
+        assert_eq!(unrelated, fingerprint);
+        assert!(cache.step_up_to_date(&project_dir, "prebuild.cc", &unrelated));
+
+        fs::write(project_dir.join("src/main.c"), "int main(void);").unwrap();
+        let changed = cache.inputs_fingerprint(&project_dir, "prebuild.cc", &inputs).unwrap();
+        assert_ne!(changed, fingerprint);
+        assert!(!cache.step_up_to_date(&project_dir, "prebuild.cc", &changed));
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn inputs_include_outputs_of_other_steps_only() {
+        let project_dir = project("step-outputs");
+        fs::create_dir_all(project_dir.join("build/module/src")).unwrap();
+        fs::create_dir_all(project_dir.join("build/gen")).unwrap();
+        fs::write(project_dir.join("build/gen/src.h"), "v1").unwrap();
+        let mut cache = BuildCache::empty(&project_dir);
+        cache.record_step(&project_dir, "prebuild.gen", "fp".to_string(), Path::new("gen.log"), vec!["build/gen/src.h".to_string()]).unwrap();
+        let inputs = ["src".to_string(), "src.h".to_string()];
+        let fingerprint = cache.inputs_fingerprint(&project_dir, "prebuild.cc", &inputs).unwrap();
+
+        // 暂存目录中的副本不是输入
+        fs::write(project_dir.join("build/module/src/main.c"), "staged").unwrap();
+        assert_eq!(cache.inputs_fingerprint(&project_dir, "prebuild.cc", &inputs).unwrap(), fingerprint);
+        // 其他步骤生成的文件是输入，对生成它的步骤自己不是
+        let own = cache.inputs_fingerprint(&project_dir, "prebuild.gen", &inputs).unwrap();
+        fs::write(project_dir.join("build/gen/src.h"), "v2").unwrap();
+        assert_ne!(cache.inputs_fingerprint(&project_dir, "prebuild.cc", &inputs).unwrap(), fingerprint);
+        assert_eq!(cache.inputs_fingerprint(&project_dir, "prebuild.gen", &inputs).unwrap(), own);
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn missing_or_modified_outputs_rerun_the_step() {
+        let project_dir = project("outputs");
+        let mut cache = BuildCache::empty(&project_dir);
+        record(&mut cache, &project_dir, "fp", &["build/main.o"]);
+        assert!(cache.step_up_to_date(&project_dir, "prebuild.cc", "fp"));
+        assert!(!cache.step_up_to_date(&project_dir, "prebuild.cc", "other"));
+
+        fs::write(project_dir.join("build/main.o"), "tampered").unwrap();
+        assert!(!cache.step_up_to_date(&project_dir, "prebuild.cc", "fp"));
+        fs::remove_file(project_dir.join("build/main.o")).unwrap();
+        assert!(!cache.step_up_to_date(&project_dir, "prebuild.cc", "fp"));
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn steps_without_outputs_always_run() {
+        let project_dir = project("no-outputs");
+        let mut cache = BuildCache::empty(&project_dir);
+        record(&mut cache, &project_dir, "fp", &[]);
+        assert!(!cache.step_up_to_date(&project_dir, "prebuild.cc", "fp"));
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn project_fingerprint_ignores_step_outputs() {
+        let project_dir = project("project");
+        fs::write(project_dir.join("generated.h"), "v1").unwrap();
+        let mut cache = BuildCache::empty(&project_dir);
+        record(&mut cache, &project_dir, "fp", &["generated.h"]);
+        let fingerprint = cache.project_fingerprint(&project_dir).unwrap();
+
+        fs::write(project_dir.join("generated.h"), "v2").unwrap();
+        fs::write(project_dir.join("build/main.o"), "new object").unwrap();
+        assert_eq!(cache.project_fingerprint(&project_dir).unwrap(), fingerprint);
+
+        fs::write(project_dir.join("README.md"), "changed readme").unwrap();
+        assert_ne!(cache.project_fingerprint(&project_dir).unwrap(), fingerprint);
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn package_up_to_date_checks_the_artifact() {
+        let project_dir = project("package");
+        let artifact = project_dir.join("build/demo.zip");
+        fs::write(&artifact, "zip").unwrap();
+        let mut cache = BuildCache::empty(&project_dir);
+        cache.record_package("module", "fp".to_string(), &artifact).unwrap();
+
+        assert!(cache.package_up_to_date("module", "fp"));
+        assert!(!cache.package_up_to_date("module", "other"));
+        assert!(!cache.package_up_to_date("sdist", "fp"));
+        fs::write(&artifact, "edited zip").unwrap();
+        assert!(!cache.package_up_to_date("module", "fp"));
+        fs::remove_file(&artifact).unwrap();
+        assert!(!cache.package_up_to_date("module", "fp"));
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+}
@@ -0,0 +1,407 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::cmds::ApmmConfig;
+use super::bundle;
+
+/// 需要检查的脚本，customize.sh 在安装时由安装器 source，其余由管理器在各启动阶段执行
+const SCRIPTS: &[&str] = &[
+    "post-fs-data.sh",
+    "post-mount.sh",
+    "service.sh",
+    "boot-completed.sh",
+    "customize.sh",
+    "uninstall.sh",
+];
+
+/// 安装时执行的脚本
+const INSTALL_SCRIPT: &str = "customize.sh";
+/// 阻塞启动的脚本
+const BLOCKING_SCRIPT: &str = "post-fs-data.sh";
+
+/// POSIX sh 中没有的命令
+const BASH_COMMANDS: &[&str] = &[
+    "[[", "function", "declare", "typeset", "shopt", "pushd", "popd", "mapfile", "readarray", "select", "source",
+];
+
+/// 循环中出现这些写法时认为它有次数或时间上限
+const BOUNDED_MARKERS: &[&str] = &["-lt", "-le", "-gt", "-ge", "timeout", "$((", "let ", "expr "];
+
+/// 命令前可能出现的关键字
+const KEYWORDS: &[&str] = &["if", "then", "else", "elif", "do", "while", "until", "!", "{", "time", "exec"];
+
+/// 脚本中的问题
+#[derive(Debug, Clone)]
+pub struct ScriptFinding {
+    /// 相对项目目录的脚本路径
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+/// 检查项目中的启动阶段脚本（包括变体覆盖的脚本）
+pub fn lint_scripts(project_dir: &Path, config: &ApmmConfig) -> Vec<ScriptFinding> {
+    let mut scripts: Vec<(&str, String)> = SCRIPTS.iter().map(|name| (*name, name.to_string())).collect();
+    for variant in &config.build_config.variants {
+        for (target, source) in &variant.scripts {
+            if let Some(stage) = SCRIPTS.iter().find(|name| *name == target)
+                && !scripts.iter().any(|(_, path)| path == source)
+            {
+                scripts.push((stage, source.clone()));
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (stage, path) in scripts {
+        if let Ok(content) = fs::read_to_string(project_dir.join(&path)) {
+            findings.extend(lint_script(project_dir, stage, &path, &content, &config.id));
+        }
+    }
+    findings
+}
+
+/// 打印检查结果，只作为警告，不会让构建失败
+pub fn report_scripts(project_dir: &Path, config: &ApmmConfig) {
+    let findings = lint_scripts(project_dir, config);
+    if findings.is_empty() {
+        return;
+    }
+    println!("🔎 Found {} problem(s) in module scripts:", findings.len());
+    for finding in &findings {
+        println!("   ⚠️  {}", finding);
+    }
+}
+
+/// 去掉注释后的一行代码
+struct CodeLine {
+    line: usize,
+    /// 保留引号内容，用于查找路径
+    text: String,
+    /// 引号内容替换为空格，用于查找命令和语法
+    code: String,
+}
+
+fn lint_script(project_dir: &Path, stage: &str, file: &str, content: &str, module_id: &str) -> Vec<ScriptFinding> {
+    let mut findings = Vec::new();
+    let mut report = |line: usize, message: String| {
+        findings.push(ScriptFinding { file: file.to_string(), line, message });
+    };
+
+    // customize.sh 由安装器 source，不需要 shebang
+    let first = content.lines().next().unwrap_or_default();
+    let mut posix = true;
+    if stage != INSTALL_SCRIPT {
+        match first.strip_prefix("#!") {
+            None => report(1, "missing shebang, add #!/system/bin/sh as the first line".to_string()),
+            Some(interpreter) => {
+                let interpreter = interpreter.trim();
+                if !(interpreter == "sh" || interpreter.ends_with("/sh") || interpreter.ends_with(" sh")) {
+                    report(1, format!("shebang '{}' is not sh, root managers run module scripts with /system/bin/sh", interpreter));
+                    posix = false;
+                }
+            },
+        }
+    }
+
+    let lines = code_lines(content);
+    let hardcoded = [format!("/data/adb/modules/{}", module_id), "/data/adb/modules/@MODID@".to_string()];
+    let module_var = if stage == INSTALL_SCRIPT { "$MODPATH" } else { "$MODDIR" };
+    // 打包时会被内联的 `source lib.sh` 不会出现在最终脚本中
+    let inlined = |line: &CodeLine| {
+        bundle::include_target(&line.text).is_some_and(|(target, _)| project_dir.join(target).is_file())
+    };
+
+    for line in &lines {
+        let commands = commands(&line.code);
+
+        if stage == BLOCKING_SCRIPT && commands.contains(&"setprop") {
+            report(line.line, "setprop in post-fs-data.sh deadlocks boot, use resetprop -n <name> <value>".to_string());
+        }
+        if stage != INSTALL_SCRIPT && commands.contains(&"ui_print") {
+            report(line.line, format!("ui_print only exists during installation, {} output goes nowhere; use echo or a log file", stage));
+        }
+        if posix && !inlined(line) && let Some(bashism) = bashism(line, &commands) {
+            report(line.line, format!("{} is a bashism, /system/bin/sh may not support it", bashism));
+        }
+        if let Some(path) = hardcoded.iter().find(|path| contains_path(&line.text, path)) {
+            report(line.line, format!("hardcoded {}, use {} so the script works wherever the module is mounted", path, module_var));
+        }
+    }
+
+    if stage == BLOCKING_SCRIPT {
+        for line in unbounded_loops(&lines) {
+            report(line, "loop without a timeout blocks boot (post-fs-data.sh has about 10 seconds), add a counter or move it to service.sh".to_string());
+        }
+    }
+
+    findings.sort_by_key(|finding| finding.line);
+    findings
+}
+
+/// 去掉注释和 here-document 内容
+fn code_lines(content: &str) -> Vec<CodeLine> {
+    let mut lines = Vec::new();
+    let mut heredoc: Option<String> = None;
+    for (index, raw) in content.lines().enumerate() {
+        if let Some(delimiter) = &heredoc {
+            if raw.trim() == delimiter {
+                heredoc = None;
+            }
+            continue;
+        }
+        let text = strip_comment(raw, false);
+        heredoc = heredoc_delimiter(&text);
+        lines.push(CodeLine { line: index + 1, code: strip_comment(raw, true), text });
+    }
+    lines
+}
+
+/// 去掉行尾注释；blank 为 true 时把引号内的内容替换为空格
+fn strip_comment(line: &str, blank: bool) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut previous = ' ';
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q && !(q == '"' && escaped) => {
+                quote = None;
+                stripped.push(c);
+            },
+            Some(_) => stripped.push(if blank { ' ' } else { c }),
+            None if c == '#' && !escaped && (previous.is_whitespace() || matches!(previous, ';' | '&' | '|')) => break,
+            None => {
+                if matches!(c, '"' | '\'') && !escaped {
+                    quote = Some(c);
+                }
+                stripped.push(c);
+            },
+        }
+        escaped = c == '\\' && !escaped;
+        previous = c;
+    }
+    stripped
+}
+
+/// `cat <<EOF` / `<<-'EOF'` 开始的 here-document 的结束标记
+fn heredoc_delimiter(text: &str) -> Option<String> {
+    let start = text.find("<<")?;
+    let rest = &text[start + 2..];
+    if rest.starts_with('<') {
+        return None;
+    }
+    let word: String = rest.trim_start_matches('-').trim_start()
+        .chars()
+        .take_while(|c| !c.is_whitespace() && !matches!(c, ';' | '|' | '&' | ')'))
+        .filter(|c| !matches!(c, '\'' | '"' | '\\'))
+        .collect();
+    (!word.is_empty()).then_some(word)
+}
+
+/// 每个简单命令的命令名（跳过关键字和变量赋值）
+fn commands(code: &str) -> Vec<&str> {
+    code.split([';', '|', '&', '(', ')', '`'])
+        .filter_map(|segment| {
+            segment.split_whitespace()
+                .find(|word| !KEYWORDS.contains(word) && !is_assignment(word))
+        })
+        .collect()
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| is_identifier(name))
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 这一行中第一个 bash 专有写法
+fn bashism(line: &CodeLine, commands: &[&str]) -> Option<String> {
+    if let Some(command) = commands.iter().find(|command| BASH_COMMANDS.contains(command)) {
+        return Some(format!("'{}'", command));
+    }
+    let code = &line.code;
+    if code.contains("<<<") {
+        return Some("here-string '<<<'".to_string());
+    }
+    if code.match_indices("&>").any(|(index, _)| !code[..index].ends_with(['>', '&'])) {
+        return Some("redirection '&>'".to_string());
+    }
+    // arr=(a b) 和 ${arr[0]}
+    if code.split_whitespace().any(|word| word.split_once("=(").is_some_and(|(name, _)| is_identifier(name))) {
+        return Some("array assignment".to_string());
+    }
+    let text = &line.text;
+    if text.match_indices("${").any(|(index, _)| {
+        let name: String = text[index + 2..].chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '_').collect();
+        !name.is_empty() && text[index + 2 + name.len()..].starts_with('[')
+    }) {
+        return Some("array expansion".to_string());
+    }
+    None
+}
+
+/// 路径后面不能紧跟模块 ID 中允许的字符（避免 /data/adb/modules/foo 匹配 foo2）
+fn contains_path(text: &str, path: &str) -> bool {
+    text.match_indices(path).any(|(index, _)| {
+        !text[index + path.len()..].starts_with(|c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    })
+}
+
+/// 没有次数或时间上限的 while / until 循环所在的行
+///
+/// `while read` / `while IFS= read` 读到输入结尾就会结束，不算无限循环
+fn unbounded_loops(lines: &[CodeLine]) -> Vec<usize> {
+    let words = |line: &CodeLine| -> Vec<String> {
+        line.code.split(|c: char| c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | ')'))
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        if !words(&lines[index]).iter().any(|word| word == "while" || word == "until") {
+            index += 1;
+            continue;
+        }
+
+        // 找到与之配对的 done，嵌套的循环一起算作这个循环的内容
+        let mut depth = 0;
+        let mut end = lines.len() - 1;
+        for (offset, line) in lines[index..].iter().enumerate() {
+            for word in words(line) {
+                match word.as_str() {
+                    "while" | "until" | "for" => depth += 1,
+                    "done" => depth -= 1,
+                    _ => {},
+                }
+            }
+            if depth <= 0 {
+                end = index + offset;
+                break;
+            }
+        }
+
+        let header = words(&lines[index]);
+        let reads_input = header.iter()
+            .skip_while(|word| *word != "while")
+            .skip(1)
+            .find(|word| !is_assignment(word))
+            .is_some_and(|word| word == "read");
+        let body: String = lines[index..=end].iter().map(|line| line.code.as_str()).collect::<Vec<_>>().join("\n");
+        if !reads_input && !BOUNDED_MARKERS.iter().any(|marker| body.contains(marker)) {
+            found.push(lines[index].line);
+        }
+        index = end + 1;
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(stage: &str, content: &str) -> Vec<String> {
+        lint_in(Path::new("/nonexistent"), stage, content)
+    }
+
+    fn lint_in(project_dir: &Path, stage: &str, content: &str) -> Vec<String> {
+        lint_script(project_dir, stage, stage, content, "demo")
+            .into_iter()
+            .map(|finding| format!("{}: {}", finding.line, finding.message))
+            .collect()
+    }
+
+    #[test]
+    fn requires_sh_shebang_except_for_customize() {
+        assert!(lint("service.sh", "echo hi\n")[0].contains("missing shebang"));
+        assert!(lint("service.sh", "#!/bin/bash\necho hi\n")[0].contains("is not sh"));
+        assert!(lint("service.sh", "#!/system/bin/sh\necho hi\n").is_empty());
+        assert!(lint("customize.sh", "ui_print hi\n").is_empty());
+    }
+
+    #[test]
+    fn flags_stage_specific_commands() {
+        let findings = lint("post-fs-data.sh", "#!/system/bin/sh\nsetprop a b\n");
+        assert!(findings[0].starts_with("2: setprop"));
+        assert!(lint("service.sh", "#!/system/bin/sh\nsetprop a b\n").is_empty());
+        assert!(lint("service.sh", "#!/system/bin/sh\n[ -f x ] && ui_print hi\n")[0].contains("ui_print"));
+    }
+
+    #[test]
+    fn flags_bashisms() {
+        for code in ["[[ -f x ]] && echo y", "source /data/local/tmp/env.sh", "cat <<< x", "echo x &> /dev/null", "arr=(a b)", "echo ${arr[0]}"] {
+            let findings = lint("service.sh", &format!("#!/system/bin/sh\n{}\n", code));
+            assert_eq!(findings.len(), 1, "{}: {:?}", code, findings);
+            assert!(findings[0].contains("bashism"), "{}", code);
+        }
+        // 引号和注释中的内容不算
+        assert!(lint("service.sh", "#!/system/bin/sh\necho '[[ x ]]' # source y\nexec 2>&1\n").is_empty());
+        // bash 脚本不检查 bashism
+        assert_eq!(lint("service.sh", "#!/bin/bash\n[[ -f x ]]\n").len(), 1);
+    }
+
+    #[test]
+    fn skips_source_lines_the_bundler_inlines() {
+        let project_dir = std::env::temp_dir().join(format!("apmm-lint-{}", std::process::id()));
+        fs::create_dir_all(project_dir.join("lib")).unwrap();
+        fs::write(project_dir.join("lib/util.sh"), "log() { echo \"$@\"; }\n").unwrap();
+
+        let script = "#!/system/bin/sh\nsource lib/util.sh\nsource \"$MODDIR/lib/util.sh\"\nsource lib/missing.sh\n";
+        let findings = lint_in(&project_dir, "service.sh", script);
+        fs::remove_dir_all(&project_dir).unwrap();
+
+        assert_eq!(findings.len(), 1, "{:?}", findings);
+        assert!(findings[0].starts_with("4: 'source'"));
+    }
+
+    #[test]
+    fn flags_hardcoded_module_paths() {
+        let findings = lint("service.sh", "#!/system/bin/sh\ncat /data/adb/modules/demo/x\n");
+        assert!(findings[0].contains("use $MODDIR"));
+        assert!(lint("customize.sh", "cp /data/adb/modules/demo/x y\n")[0].contains("use $MODPATH"));
+        assert!(lint("service.sh", "#!/system/bin/sh\nls /data/adb/modules/demo2\n").is_empty());
+    }
+
+    #[test]
+    fn flags_unbounded_loops_in_post_fs_data() {
+        let script = "#!/system/bin/sh\nwhile [ ! -f /sdcard/x ]; do\n  sleep 1\ndone\n";
+        let findings = lint("post-fs-data.sh", script);
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].starts_with("2: loop without a timeout"));
+        // service.sh 不阻塞启动
+        assert!(lint("service.sh", script).is_empty());
+    }
+
+    #[test]
+    fn accepts_bounded_and_input_driven_loops() {
+        for script in [
+            "i=0\nwhile [ $i -lt 10 ]; do\n  i=$((i + 1))\ndone\n",
+            "until timeout 5 getprop x; do sleep 1; done\n",
+            "while read -r l; do\n  echo \"$l\"\ndone < /proc/cmdline\n",
+            "while IFS= read -r l; do echo \"$l\"; done < /proc/mounts\n",
+            "cat /proc/mounts | while IFS=: read -r a b; do echo $a; done\n",
+        ] {
+            let findings = lint("post-fs-data.sh", &format!("#!/system/bin/sh\n{}", script));
+            assert!(findings.is_empty(), "{}: {:?}", script, findings);
+        }
+    }
+
+    #[test]
+    fn ignores_heredoc_content() {
+        let script = "#!/system/bin/sh\ncat <<EOF > x\n[[ not code ]]\nsetprop a b\nEOF\necho done\n";
+        assert!(lint("post-fs-data.sh", script).is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::Path;
+
+use crate::cmds::ApmmConfig;
+use crate::init::{validate_module_props, PropError};
+
+/// 模块根目录下的属性文件
+pub const MODULE_PROP: &str = "module.prop";
+
+/// 在执行任何构建步骤之前检查 module.prop 能否被打包
+pub fn check_module_prop(config: &ApmmConfig) -> Result<(), String> {
+    // init 生成的 YYYYMMDDHHMM 版本代码超出 32 位整数，只提示不阻止打包
+    if config.version_code > i64::from(i32::MAX) {
+        println!("⚠️  versionCode {} exceeds {}, some managers read it as a 32-bit integer", config.version_code, i32::MAX);
+    }
+    validate(config)
+}
+
+/// 按写入 zip 时的值（去掉首尾空白）校验模块属性，build 和 check 共用
+///
+/// versionCode 超出 32 位时按边界值校验，是否提示由调用方决定
+pub fn validate_props(config: &ApmmConfig) -> Result<(), Vec<PropError>> {
+    let clamped = config.version_code.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32;
+    validate_module_props(
+        config.id.trim(),
+        config.name.trim(),
+        config.version.trim(),
+        clamped,
+        config.author.trim(),
+        config.description.trim(),
+        config.update_json.trim(),
+    )
+}
+
+fn validate(config: &ApmmConfig) -> Result<(), String> {
+    validate_props(config).map_err(|errors| {
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        format!("module.prop cannot be packaged: {}", messages.join("; "))
+    })
+}
+
+/// 生成写入 zip 的 module.prop
+///
+/// Magisk / KernelSU / APatch 按行读取 `key=value`，不认识引号和 TOML 节区，
+/// 因此只写出管理器识别的键，值不加引号；项目根目录的 module.prop 保持不变
+pub fn render_module_prop(config: &ApmmConfig) -> Result<String, String> {
+    validate(config)?;
+    let (id, name, version, author, description, update_json) = (
+        config.id.trim(),
+        config.name.trim(),
+        config.version.trim(),
+        config.author.trim(),
+        config.description.trim(),
+        config.update_json.trim(),
+    );
+
+    let version_code = config.version_code.to_string();
+    let mut props = vec![
+        ("id", id),
+        ("name", name),
+        ("version", version),
+        ("versionCode", version_code.as_str()),
+        ("author", author),
+        ("description", description),
+    ];
+    if !update_json.is_empty() {
+        props.push(("updateJson", update_json));
+    }
+
+    Ok(props.into_iter().map(|(key, value)| format!("{}={}\n", key, value)).collect())
+}
+
+/// 用生成的内容覆盖暂存目录中的 module.prop
+pub fn write_module_prop(staging_dir: &Path, content: &str) -> Result<(), String> {
+    let path = staging_dir.join(MODULE_PROP);
+    fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
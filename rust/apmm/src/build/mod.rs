@@ -0,0 +1,212 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::cmds::ApmmConfig;
+use crate::workspace::ApmmWorkspace;
+use cache::BuildCache;
+
+// 导入子模块
+mod backend;
+mod bundle;
+mod cache;
+mod lint;
+mod manifest;
+mod native;
+mod package;
+mod pattern;
+mod report;
+mod reproducible;
+mod requires;
+mod sdist;
+mod steps;
+mod template;
+mod unix;
+mod watch;
+
+// 重新导出供其他命令使用的功能
+pub use cache::hash_file;
+pub use lint::lint_scripts;
+pub use manifest::validate_props;
+pub use package::{BUILD_DIR, DIST_DIR};
+pub use unix::parse_mode;
+
+/// build 命令选项
+#[derive(Debug, Default)]
+struct BuildOptions {
+    /// 额外生成源码包
+    sdist: bool,
+    /// 忽略 .apmm/ 中的增量构建缓存
+    force: bool,
+    /// 只构建指定的变体
+    variant: Option<String>,
+    /// 额外把体积报告写成 JSON
+    report_json: bool,
+    /// 监视文件变化并持续重新构建
+    watch: bool,
+    /// 监视模式下每次构建后安装模块
+    install: bool,
+    /// 安装时跳过签名校验
+    allow_unsigned: bool,
+    /// 构建所在工作区的所有成员
+    workspace: bool,
+}
+
+impl BuildOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = BuildOptions::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--sdist" => options.sdist = true,
+                "--force" | "-f" => options.force = true,
+                "--variant" => {
+                    let name = args.next().ok_or("--variant requires a variant name")?;
+                    options.variant = Some(name.clone());
+                },
+                "--report-json" => options.report_json = true,
+                "--watch" | "-w" => options.watch = true,
+                "--install" => options.install = true,
+                "--allow-unsigned" => options.allow_unsigned = true,
+                "--workspace" => options.workspace = true,
+                _ => return Err(format!("Unknown build option: {}", arg)),
+            }
+        }
+        if options.install && !options.watch {
+            return Err("--install can only be used with --watch".to_string());
+        }
+        if options.allow_unsigned && !options.install {
+            return Err("--allow-unsigned can only be used with --install".to_string());
+        }
+        // 工作区构建逐个成员构建一次就结束，不支持监视
+        if options.workspace && options.watch {
+            return Err("--workspace cannot be used with --watch".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// build 命令处理
+pub fn cmd_build(args: &[String]) -> Result<String, String> {
+    let options = BuildOptions::parse(args)?;
+
+    let project_dir = env::current_dir()
+        .map_err(|e| format!("Failed to get current directory: {}", e))?;
+    if options.workspace {
+        return build_workspace(&project_dir, &options);
+    }
+    if !project_dir.join("module.prop").exists() {
+        return Err("module.prop not found in current directory".to_string());
+    }
+
+    if options.watch {
+        return watch::watch(&project_dir, &options);
+    }
+    build_once(&project_dir, &options).map(|(success_msg, _)| success_msg)
+}
+
+/// 按成员声明顺序依次构建工作区，任一成员失败即停止
+fn build_workspace(start: &Path, options: &BuildOptions) -> Result<String, String> {
+    let workspace = ApmmWorkspace::find(start)?
+        .ok_or("No apmm-workspace.toml or [workspace] found in this directory or its parents")?;
+    let members = workspace.members()?;
+    if members.is_empty() {
+        return Err("Workspace has no members".to_string());
+    }
+
+    println!("🗂️  Workspace: {} ({} members)", workspace.root.display(), members.len());
+    for (index, member) in members.iter().enumerate() {
+        let name = workspace.relative(member);
+        println!();
+        println!("📁 [{}/{}] {}", index + 1, members.len(), name);
+        build_once(member, options).map_err(|e| format!("Workspace member {} failed: {}", name, e))?;
+    }
+
+    let success_msg = format!("Built {} workspace members", members.len());
+    println!();
+    println!("✅ {}", success_msg);
+    Ok(success_msg)
+}
+
+/// 完整构建一次，返回成功信息和默认打包生成的产物
+fn build_once(project_dir: &Path, options: &BuildOptions) -> Result<(String, Vec<PathBuf>), String> {
+    println!("🔨 Building APMM module...");
+    let config = ApmmConfig::load_from(project_dir)?;
+    println!("📦 Module: {} {}", config.name, config.version);
+    manifest::check_module_prop(&config)?;
+
+    if let Some(name) = &options.variant
+        && !config.build_config.variants.iter().any(|v| &v.name == name)
+    {
+        return Err(format!("Unknown build variant: {}", name));
+    }
+
+    // [[build.build]] 替代了默认打包，变体只在默认打包中生效
+    if !config.build_config.build_steps.is_empty() && !config.build_config.variants.is_empty() {
+        if options.variant.is_some() {
+            return Err("--variant cannot be used when [[build.build]] steps replace the default packaging".to_string());
+        }
+        println!("⚠️  [build.variants] is ignored because [[build.build]] steps replace the default packaging");
+    }
+
+    if !config.build_config.system_requires.is_empty() {
+        println!("📋 Checking build requirements...");
+        requires::check_requirements(&config.build_config.system_requires)?;
+    }
+
+    if !config.build_config.native.is_empty() {
+        native::check_customize_script(project_dir);
+    }
+    lint::report_scripts(project_dir, &config);
+
+    let mut cache = if options.force {
+        BuildCache::empty(project_dir)
+    } else {
+        BuildCache::load(project_dir)
+    };
+
+    // 无论成功与否都保存缓存，已经成功的步骤下次可以跳过
+    let result = run_build(project_dir, &config, options, &mut cache);
+    cache.save()?;
+    let artifacts = result?;
+
+    let success_msg = format!("Module {} {} built successfully!", config.name, config.version);
+    println!("✅ {}", success_msg);
+    Ok((success_msg, artifacts))
+}
+
+/// 执行完整的构建流程：prebuild -> build（或默认打包）-> sdist -> postbuild，返回默认打包的产物
+fn run_build(project_dir: &Path, config: &ApmmConfig, options: &BuildOptions, cache: &mut BuildCache) -> Result<Vec<PathBuf>, String> {
+    let build_config = &config.build_config;
+
+    // 执行预构建步骤
+    if !build_config.prebuild_steps.is_empty() {
+        println!("⚙️  Running prebuild steps...");
+        steps::run_steps("prebuild", &build_config.prebuild_steps, project_dir, config, cache)?;
+    }
+
+    // 执行构建步骤：[[build.build]] 非空时完全替代默认打包流程
+    println!("🔧 Running build steps...");
+    let mut artifacts = Vec::new();
+    if build_config.build_steps.is_empty() {
+        artifacts = package::package_module(project_dir, config, cache, options.variant.as_deref(), options.report_json)?;
+        for artifact in &artifacts {
+            println!("   Artifact: {}", artifact.display());
+        }
+    } else {
+        steps::run_steps("build", &build_config.build_steps, project_dir, config, cache)?;
+    }
+
+    // 生成源码包
+    if options.sdist {
+        let archive = sdist::build_sdist(project_dir, config)?;
+        println!("   Artifact: {}", archive.display());
+    }
+
+    // 执行后构建步骤
+    if !build_config.postbuild_steps.is_empty() {
+        println!("🧹 Running postbuild steps...");
+        steps::run_steps("postbuild", &build_config.postbuild_steps, project_dir, config, cache)?;
+    }
+
+    Ok(artifacts)
+}
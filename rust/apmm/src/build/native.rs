@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 原生二进制在模块中的目录，customize.sh 安装时从 bin/<ABI>/ 中选择
+pub const NATIVE_DIR: &str = "bin";
+
+/// 支持的 ABI（与安装时的 $ABI 一致）
+pub const SUPPORTED_ABIS: &[&str] = &["arm64-v8a", "armeabi-v7a", "x86", "x86_64"];
+
+/// 按 [build.native] 解析需要打包的原生二进制，布局为 bin/<ABI>/<文件名>
+pub fn native_files(project_dir: &Path, native: &[(String, Vec<String>)]) -> Result<Vec<(PathBuf, String)>, String> {
+    let mut files = Vec::new();
+
+    for (abi, paths) in native {
+        if !SUPPORTED_ABIS.contains(&abi.as_str()) {
+            return Err(format!("Unsupported ABI '{}' in [build.native], expected one of: {}", abi, SUPPORTED_ABIS.join(", ")));
+        }
+
+        for path in paths {
+            let source = project_dir.join(path);
+            if !source.is_file() {
+                return Err(format!("Native binary for {} not found: {}", abi, path));
+            }
+            let file_name = source.file_name()
+                .ok_or_else(|| format!("Invalid native binary path: {}", path))?
+                .to_string_lossy()
+                .to_string();
+            files.push((source, format!("{}/{}/{}", NATIVE_DIR, abi, file_name)));
+        }
+    }
+
+    Ok(files)
+}
+
+/// 检查 customize.sh 是否包含按 ABI 选择二进制的逻辑（旧项目生成的脚本没有）
+pub fn check_customize_script(project_dir: &Path) {
+    let customize = project_dir.join("customize.sh");
+    let selects_abi = fs::read_to_string(&customize)
+        .map(|content| content.contains("$ABI"))
+        .unwrap_or(false);
+    if !selects_abi {
+        println!("⚠️  [build.native] is set but customize.sh does not select binaries by $ABI");
+        println!("   All ABIs will be installed; see the customize.sh generated by 'apmm init' for the $ABI selection block");
+    }
+}
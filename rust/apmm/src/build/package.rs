@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::cmds::{ApmmConfig, BuildConfig, BuildVariant, FileRules};
+use super::cache::BuildCache;
+use super::{backend, bundle, manifest, native, pattern, report, reproducible, template, unix};
+
+/// 构建输出目录
+pub const BUILD_DIR: &str = "build";
+/// 模块暂存目录
+pub const MODULE_DIR: &str = "module";
+/// 最终产物目录
+pub const DIST_DIR: &str = "dist";
+
+/// 无论 exclude 如何配置都不会被打包的顶级目录
+const ALWAYS_SKIP: &[&str] = &[BUILD_DIR, ".apmm", ".git"];
+
+/// 打包模块，声明了 [build.variants.*] 时每个变体生成一个 zip
+///
+/// `only` 指定时只打包对应名称的变体；新生成的 zip 会打印体积报告，report_json 为 true 时同时写入 JSON
+pub fn package_module(project_dir: &Path, config: &ApmmConfig, cache: &mut BuildCache, only: Option<&str>, report_json: bool) -> Result<Vec<PathBuf>, String> {
+    let variants = &config.build_config.variants;
+    if variants.is_empty() {
+        if let Some(name) = only {
+            return Err(format!("Unknown build variant: {}", name));
+        }
+        return package_variant(project_dir, config, None, cache, report_json);
+    }
+
+    let mut artifacts = Vec::new();
+    for variant in variants {
+        if only.is_some_and(|name| name != variant.name) {
+            continue;
+        }
+        println!("🎯 Variant: {}", variant.name);
+        artifacts.extend(package_variant(project_dir, config, Some(variant), cache, report_json)?);
+    }
+
+    if artifacts.is_empty() {
+        return Err(format!("Unknown build variant: {}", only.unwrap_or_default()));
+    }
+    Ok(artifacts)
+}
+
+/// 打包单个变体：暂存文件 -> 压缩 -> 复制到 build/dist/
+///
+/// 输入文件与上次打包时完全一致且产物未被改动时跳过暂存、压缩和体积报告；
+/// 使用外部构建后端时暂存后交给后端打包，不使用缓存
+fn package_variant(project_dir: &Path, config: &ApmmConfig, variant: Option<&BuildVariant>, cache: &mut BuildCache, report_json: bool) -> Result<Vec<PathBuf>, String> {
+    let build_dir = project_dir.join(BUILD_DIR);
+    let dist_dir = build_dir.join(DIST_DIR);
+
+    let (staging_dir, zip_name) = match variant {
+        Some(variant) => (
+            build_dir.join(format!("{}-{}", MODULE_DIR, variant.name)),
+            format!("{}-{}-{}.zip", config.id, config.version, variant.name),
+        ),
+        None => (
+            build_dir.join(MODULE_DIR),
+            format!("{}-{}.zip", config.id, config.version),
+        ),
+    };
+    let zip_path = build_dir.join(&zip_name);
+    let dist_path = dist_dir.join(&zip_name);
+
+    let files = resolve_module_files(project_dir, &config.build_config, variant)?;
+    let vars = template::template_vars(config);
+    let module_prop = manifest::render_module_prop(config)?;
+    let backend = config.build_config.build_backend.as_str();
+
+    if backend != backend::BUILTIN_BACKEND {
+        println!("📂 Staging module files into {}...", staging_dir.display());
+        stage_files(&staging_dir, &files, &vars)?;
+        manifest::write_module_prop(&staging_dir, &module_prop)?;
+        println!("   Staged {} files", files.len());
+
+        fs::create_dir_all(&dist_dir)
+            .map_err(|e| format!("Failed to create {}: {}", dist_dir.display(), e))?;
+        let mut staged: Vec<String> = files.into_iter().map(|(_, rel_path)| rel_path).collect();
+        if !staged.iter().any(|rel_path| rel_path == manifest::MODULE_PROP) {
+            staged.push(manifest::MODULE_PROP.to_string());
+        }
+        let variant_name = variant.map(|v| v.name.as_str());
+        let artifacts = backend::run_backend(backend, project_dir, config, &staging_dir, &dist_dir, variant_name, staged)?;
+        for artifact in artifacts.iter().filter(|path| path.extension().is_some_and(|ext| ext == "zip")) {
+            report::report_artifact(project_dir, artifact, report_json)?;
+        }
+        return Ok(artifacts);
+    }
+
+    // 模板变量或权限规则变化时（例如只改了 [build.vars]）也需要重新打包
+    let modes = &config.build_config.modes;
+    let epoch = reproducible::build_epoch();
+    let salt = format!("{}\n{}\n{:?}\n{:?}\n{}", zip_name, epoch, vars, modes, module_prop);
+    let fingerprint = cache.files_fingerprint(project_dir, &files, &salt)?;
+    if cache.package_up_to_date(&zip_name, &fingerprint) {
+        println!("📦 {} is up to date, skipping staging and compression", zip_name);
+        return Ok(vec![dist_path]);
+    }
+
+    println!("📂 Staging module files into {}...", staging_dir.display());
+    stage_files(&staging_dir, &files, &vars)?;
+    manifest::write_module_prop(&staging_dir, &module_prop)?;
+    println!("   Staged {} files", files.len());
+
+    println!("🗜️  Compressing {}...", zip_name);
+    zip_dir(&staging_dir, &zip_path, modes, epoch)?;
+
+    fs::create_dir_all(&dist_dir)
+        .map_err(|e| format!("Failed to create {}: {}", dist_dir.display(), e))?;
+    fs::copy(&zip_path, &dist_path)
+        .map_err(|e| format!("Failed to copy artifact to {}: {}", dist_path.display(), e))?;
+
+    cache.record_package(&zip_name, fingerprint, &dist_path)?;
+    report::report_artifact(project_dir, &dist_path, report_json)?;
+    Ok(vec![dist_path])
+}
+
+/// 解析模块 zip 的全部内容：合并 [build.module] 与变体自身的规则，
+/// 应用变体的脚本覆盖，并把 [build.native] 中的原生二进制移到 bin/<ABI>/ 下
+fn resolve_module_files(project_dir: &Path, build_config: &BuildConfig, variant: Option<&BuildVariant>) -> Result<Vec<(PathBuf, String)>, String> {
+    let base = &build_config.module;
+    let mut files = match variant {
+        Some(variant) => resolve_files(project_dir, &FileRules {
+            extra: base.extra.iter().chain(&variant.rules.extra).cloned().collect(),
+            exclude: base.exclude.iter().chain(&variant.rules.exclude).cloned().collect(),
+        })?,
+        None => resolve_files(project_dir, base)?,
+    };
+
+    // 原生二进制只放在 bin/<ABI>/ 下，不再按原路径重复打包
+    let mut overrides = native::native_files(project_dir, &build_config.native)?;
+    files.retain(|(source, _)| !overrides.iter().any(|(native, _)| native == source));
+    if let Some(variant) = variant {
+        for (target, source) in &variant.scripts {
+            let source_path = project_dir.join(source);
+            if !source_path.is_file() {
+                return Err(format!("Script override for variant '{}' not found: {}", variant.name, source));
+            }
+            overrides.push((source_path, target.trim_start_matches("./").to_string()));
+        }
+    }
+
+    for (source, target) in overrides {
+        files.retain(|(_, rel_path)| *rel_path != target);
+        files.push((source, target));
+    }
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+
+    Ok(files)
+}
+
+/// 把解析好的文件（源路径, 包内路径）复制到清空后的暂存目录，
+/// 统一脚本换行符、内联脚本包含的辅助文件并替换模板占位符
+pub fn stage_files(staging_dir: &Path, files: &[(PathBuf, String)], vars: &[(String, String)]) -> Result<(), String> {
+    if staging_dir.exists() {
+        fs::remove_dir_all(staging_dir)
+            .map_err(|e| format!("Failed to clean {}: {}", staging_dir.display(), e))?;
+    }
+    fs::create_dir_all(staging_dir)
+        .map_err(|e| format!("Failed to create {}: {}", staging_dir.display(), e))?;
+
+    for (source, rel_path) in files {
+        copy_file(source, &staging_dir.join(rel_path))?;
+    }
+
+    let rel_paths: Vec<&str> = files.iter().map(|(_, rel_path)| rel_path.as_str()).collect();
+    let converted = unix::normalize_line_endings(staging_dir, &rel_paths)?;
+    if converted > 0 {
+        println!("   Converted CRLF line endings to LF in {} files", converted);
+    }
+    bundle::bundle_staged(staging_dir, &rel_paths)?;
+    let expanded = template::expand_staged(staging_dir, &rel_paths, vars)?;
+    if expanded > 0 {
+        println!("   Expanded template variables in {} files", expanded);
+    }
+
+    Ok(())
+}
+
+/// 按 extra / exclude 规则解析需要打包的文件，返回 (源路径, 包内相对路径)
+pub fn resolve_files(project_dir: &Path, rules: &FileRules) -> Result<Vec<(PathBuf, String)>, String> {
+    let mut files: Vec<(PathBuf, String)> = collect_files(project_dir, &rules.exclude)?
+        .into_iter()
+        .map(|rel_path| (project_dir.join(&rel_path), rel_path))
+        .collect();
+
+    // extra 中列出的路径总是会被打包，即使它们匹配了 exclude
+    for extra in &rules.extra {
+        let source = project_dir.join(extra);
+        if !source.exists() {
+            return Err(format!("Extra path not found: {}", extra));
+        }
+        let target = extra_target(project_dir, &source)?;
+        if source.is_dir() {
+            for rel_path in collect_files(&source, &[])? {
+                files.push((source.join(&rel_path), format!("{}/{}", target, rel_path)));
+            }
+        } else {
+            files.push((source, target));
+        }
+    }
+
+    // 同一路径只保留最后一次出现（extra 覆盖普通文件）
+    let mut seen = HashSet::new();
+    files.reverse();
+    files.retain(|(_, rel_path)| seen.insert(rel_path.clone()));
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+
+    Ok(files)
+}
+
+/// 递归收集目录下所有未被排除的文件（相对路径，使用 `/` 分隔，按字典序排列）
+pub fn collect_files(root: &Path, exclude: &[String]) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    walk(root, "", exclude, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn walk(dir: &Path, prefix: &str, exclude: &[String], files: &mut Vec<String>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if prefix.is_empty() && ALWAYS_SKIP.contains(&name.as_str()) {
+            continue;
+        }
+
+        let rel_path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        if pattern::matches_any(exclude, &rel_path) {
+            continue;
+        }
+
+        let path = entry.path();
+        if path.is_dir() {
+            walk(&path, &rel_path, exclude, files)?;
+        } else {
+            files.push(rel_path);
+        }
+    }
+
+    Ok(())
+}
+
+/// 计算 extra 路径在暂存目录中的位置：项目内的路径保持原结构，项目外的只保留文件名
+fn extra_target(project_dir: &Path, source: &Path) -> Result<String, String> {
+    let inside = source.strip_prefix(project_dir).ok()
+        .filter(|rel| rel.components().all(|c| matches!(c, Component::Normal(_))));
+    let target = match inside {
+        Some(rel) if !rel.as_os_str().is_empty() => rel.to_path_buf(),
+        _ => source.file_name()
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("Invalid extra path: {}", source.display()))?,
+    };
+    Ok(target.to_string_lossy().replace('\\', "/"))
+}
+
+fn copy_file(source: &Path, target: &Path) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::copy(source, target)
+        .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+    Ok(())
+}
+
+/// 将目录压缩为 zip 文件，返回写入的文件数量
+///
+/// 为保证同一份输入得到字节完全相同的 zip：条目按路径排序，
+/// 时间戳统一为 epoch，权限与压缩参数固定
+pub fn zip_dir(source_dir: &Path, zip_path: &Path, modes: &[(String, u32)], epoch: i64) -> Result<usize, String> {
+    let file = File::create(zip_path)
+        .map_err(|e| format!("Failed to create {}: {}", zip_path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let base_options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .compression_level(Some(6))
+        .last_modified_time(reproducible::zip_datetime(epoch));
+    let dir_options = base_options.unix_permissions(unix::DIR_MODE);
+
+    let files = collect_files(source_dir, &[])?;
+    let mut added_dirs: Vec<String> = Vec::new();
+
+    for rel_path in &files {
+        // 先写入父目录条目，保证解压时目录结构完整
+        let mut dir_end = 0;
+        while let Some(pos) = rel_path[dir_end..].find('/') {
+            dir_end += pos + 1;
+            let dir = &rel_path[..dir_end];
+            if !added_dirs.iter().any(|d| d == dir) {
+                zip.add_directory(dir, dir_options)
+                    .map_err(|e| format!("Failed to add directory {}: {}", dir, e))?;
+                added_dirs.push(dir.to_string());
+            }
+        }
+
+        let options = base_options.unix_permissions(unix::file_mode(rel_path, modes));
+        zip.start_file(rel_path.as_str(), options)
+            .map_err(|e| format!("Failed to add {}: {}", rel_path, e))?;
+        let mut source = File::open(source_dir.join(rel_path))
+            .map_err(|e| format!("Failed to open {}: {}", rel_path, e))?;
+        io::copy(&mut source, &mut zip)
+            .map_err(|e| format!("Failed to compress {}: {}", rel_path, e))?;
+    }
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish {}: {}", zip_path.display(), e))?;
+
+    Ok(files.len())
+}
@@ -0,0 +1,72 @@
+/// 判断相对路径是否匹配任一模式
+pub fn matches_any(patterns: &[String], rel_path: &str) -> bool {
+    patterns.iter().any(|p| matches(p, rel_path))
+}
+
+/// 判断相对路径是否匹配模式（类似 .gitignore 的规则）
+///
+/// - 不含 `/` 的模式匹配路径中的任意一级，例如 `.*` 排除所有隐藏文件
+/// - 含 `/` 的模式锚定到项目根目录，匹配该路径本身或其父目录
+/// - 支持 `*`、`?` 以及跨目录的 `**`
+pub fn matches(pattern: &str, rel_path: &str) -> bool {
+    let pattern = pattern.trim();
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    let pattern = pattern.trim_end_matches('/');
+    if pattern.is_empty() {
+        return false;
+    }
+
+    if pattern.contains('/') {
+        let pattern = pattern.trim_start_matches('/');
+        let mut end = 0;
+        for component in rel_path.split('/') {
+            end += component.len();
+            if glob_match(pattern, &rel_path[..end]) {
+                return true;
+            }
+            end += 1;
+        }
+        false
+    } else {
+        rel_path.split('/').any(|component| glob_match(pattern, component))
+    }
+}
+
+/// 通配符匹配
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    match_from(&pattern, &text)
+}
+
+fn match_from(p: &[char], t: &[char]) -> bool {
+    match p.first() {
+        None => t.is_empty(),
+        Some('*') if p.get(1) == Some(&'*') => {
+            if p.get(2) == Some(&'/') {
+                // "**/" 可以匹配零个或多个目录
+                let rest = &p[3..];
+                match_from(rest, t)
+                    || (0..t.len()).any(|i| t[i] == '/' && match_from(rest, &t[i + 1..]))
+            } else {
+                let rest = &p[2..];
+                (0..=t.len()).any(|i| match_from(rest, &t[i..]))
+            }
+        },
+        Some('*') => {
+            // 单个 "*" 不跨越目录分隔符
+            let rest = &p[1..];
+            for i in 0..=t.len() {
+                if match_from(rest, &t[i..]) {
+                    return true;
+                }
+                if i < t.len() && t[i] == '/' {
+                    break;
+                }
+            }
+            false
+        },
+        Some('?') => !t.is_empty() && t[0] != '/' && match_from(&p[1..], &t[1..]),
+        Some(c) => t.first() == Some(c) && match_from(&p[1..], &t[1..]),
+    }
+}
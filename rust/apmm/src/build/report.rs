@@ -0,0 +1,400 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zip::ZipArchive;
+
+use super::cache::hex;
+use super::{pattern, BUILD_DIR};
+
+/// 历史报告目录（位于 .apmm/ 下），用于和上一次构建比较
+const REPORT_DIR: &str = "reports";
+/// 列出的最大文件数
+const TOP_FILES: usize = 10;
+/// 列出的变化最大的文件数
+const TOP_CHANGES: usize = 5;
+/// ELF 文件头
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+/// 未 strip 的 ELF 中包含的调试段名
+const DEBUG_SECTION: &[u8] = b".debug_info";
+/// 通常不应该出现在模块中的路径
+const SUSPICIOUS: &[&str] = &["node_modules", "__pycache__", "*.map", "*.pdb", "*.dSYM", "*.debug", "*.o", "*.a"];
+
+/// zip 中的单个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EntryInfo {
+    path: String,
+    size: u64,
+    compressed: u64,
+    sha256: String,
+}
+
+/// 顶级目录的合计
+#[derive(Debug, Serialize)]
+struct DirTotal {
+    path: String,
+    files: usize,
+    size: u64,
+    compressed: u64,
+}
+
+/// 内容相同的一组文件
+#[derive(Debug, Serialize)]
+struct Duplicate {
+    sha256: String,
+    size: u64,
+    paths: Vec<String>,
+}
+
+/// 单个文件压缩后大小的变化（新增文件 previous 为 0，删除的文件 current 为 0）
+#[derive(Debug, Serialize)]
+struct FileDelta {
+    path: String,
+    previous: u64,
+    current: u64,
+}
+
+/// 与上一次构建的比较
+#[derive(Debug, Serialize)]
+struct Growth {
+    previous_zip_size: u64,
+    delta: i64,
+    changed: Vec<FileDelta>,
+}
+
+/// 产物体积报告
+#[derive(Debug, Serialize)]
+struct SizeReport {
+    artifact: String,
+    zip_size: u64,
+    files: usize,
+    size: u64,
+    compressed: u64,
+    largest: Vec<EntryInfo>,
+    directories: Vec<DirTotal>,
+    duplicates: Vec<Duplicate>,
+    suspicious: Vec<String>,
+    growth: Option<Growth>,
+}
+
+/// 保存在 .apmm/reports/ 中的历史记录
+#[derive(Debug, Serialize, Deserialize)]
+struct ReportRecord {
+    zip_size: u64,
+    entries: Vec<EntryInfo>,
+}
+
+/// 分析 zip 产物并打印体积报告，json 为 true 时额外写入 build/<产物名>.report.json
+pub fn report_artifact(project_dir: &Path, artifact: &Path, json: bool) -> Result<(), String> {
+    let name = artifact.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let zip_size = fs::metadata(artifact)
+        .map_err(|e| format!("Failed to read {}: {}", artifact.display(), e))?
+        .len();
+    let (entries, suspicious) = read_entries(artifact)?;
+
+    let record_path = project_dir.join(".apmm").join(REPORT_DIR).join(format!("{}.json", name));
+    let previous: Option<ReportRecord> = fs::read_to_string(&record_path).ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+
+    let report = SizeReport {
+        artifact: name.clone(),
+        zip_size,
+        files: entries.len(),
+        size: entries.iter().map(|e| e.size).sum(),
+        compressed: entries.iter().map(|e| e.compressed).sum(),
+        largest: largest(&entries),
+        directories: directories(&entries),
+        duplicates: duplicates(&entries),
+        suspicious,
+        growth: previous.map(|previous| growth(&previous, zip_size, &entries)),
+    };
+    print_report(&report);
+
+    if json {
+        let json_path = project_dir.join(BUILD_DIR).join(format!("{}.report.json", name.trim_end_matches(".zip")));
+        let content = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("Failed to serialize size report: {}", e))?;
+        fs::write(&json_path, content + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", json_path.display(), e))?;
+        println!("   Report: {}", json_path.display());
+    }
+
+    // 记录本次结果供下次比较
+    if let Some(dir) = record_path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let record = ReportRecord { zip_size, entries };
+    let content = serde_json::to_string(&record)
+        .map_err(|e| format!("Failed to serialize size report: {}", e))?;
+    fs::write(&record_path, content)
+        .map_err(|e| format!("Failed to write {}: {}", record_path.display(), e))
+}
+
+/// 读取 zip 中的所有文件，同时找出可疑的内容
+fn read_entries(artifact: &Path) -> Result<(Vec<EntryInfo>, Vec<String>), String> {
+    let file = File::open(artifact)
+        .map_err(|e| format!("Failed to open {}: {}", artifact.display(), e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read {}: {}", artifact.display(), e))?;
+    let suspicious_patterns: Vec<String> = SUSPICIOUS.iter().map(|p| p.to_string()).collect();
+
+    let mut entries = Vec::new();
+    let mut suspicious = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)
+            .map_err(|e| format!("Failed to read {}: {}", artifact.display(), e))?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let path = entry.name().to_string();
+        let mut scanner = EntryScanner::default();
+        io::copy(&mut entry, &mut scanner)
+            .map_err(|e| format!("Failed to read {} from {}: {}", path, artifact.display(), e))?;
+
+        if pattern::matches_any(&suspicious_patterns, &path) {
+            suspicious.push(path.clone());
+        } else if scanner.is_unstripped_elf() {
+            suspicious.push(format!("{} (unstripped debug info)", path));
+        }
+
+        entries.push(EntryInfo {
+            path,
+            size: entry.size(),
+            compressed: entry.compressed_size(),
+            sha256: hex(&scanner.hasher.finalize()),
+        });
+    }
+
+    Ok((entries, suspicious))
+}
+
+/// 边解压边计算哈希并查找调试段，不把整个文件读入内存
+#[derive(Default)]
+struct EntryScanner {
+    hasher: Sha256,
+    /// 文件开头的几个字节，用于判断是否为 ELF
+    head: Vec<u8>,
+    /// 上一块末尾的字节，避免调试段名跨块时漏掉
+    tail: Vec<u8>,
+    debug_info: bool,
+}
+
+impl EntryScanner {
+    /// 带调试信息的 ELF（未 strip 的 debug 构建）
+    fn is_unstripped_elf(&self) -> bool {
+        self.head == ELF_MAGIC && self.debug_info
+    }
+}
+
+impl Write for EntryScanner {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+
+        let missing = ELF_MAGIC.len() - self.head.len();
+        self.head.extend_from_slice(&buf[..missing.min(buf.len())]);
+
+        if !self.debug_info {
+            let mut window = std::mem::take(&mut self.tail);
+            window.extend_from_slice(buf);
+            self.debug_info = window.windows(DEBUG_SECTION.len()).any(|w| w == DEBUG_SECTION);
+            let keep = window.len().saturating_sub(DEBUG_SECTION.len() - 1);
+            self.tail = window.split_off(keep);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn largest(entries: &[EntryInfo]) -> Vec<EntryInfo> {
+    let mut sorted = entries.to_vec();
+    sorted.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    sorted.truncate(TOP_FILES);
+    sorted
+}
+
+/// 按顶级目录汇总，根目录下的文件归入 "."
+fn directories(entries: &[EntryInfo]) -> Vec<DirTotal> {
+    let mut totals: BTreeMap<String, DirTotal> = BTreeMap::new();
+    for entry in entries {
+        let dir = match entry.path.split_once('/') {
+            Some((dir, _)) => format!("{}/", dir),
+            None => ".".to_string(),
+        };
+        let total = totals.entry(dir.clone())
+            .or_insert(DirTotal { path: dir, files: 0, size: 0, compressed: 0 });
+        total.files += 1;
+        total.size += entry.size;
+        total.compressed += entry.compressed;
+    }
+
+    let mut totals: Vec<DirTotal> = totals.into_values().collect();
+    totals.sort_by(|a, b| b.compressed.cmp(&a.compressed).then_with(|| a.path.cmp(&b.path)));
+    totals
+}
+
+fn duplicates(entries: &[EntryInfo]) -> Vec<Duplicate> {
+    let mut groups: BTreeMap<&str, Vec<&EntryInfo>> = BTreeMap::new();
+    for entry in entries.iter().filter(|e| e.size > 0) {
+        groups.entry(entry.sha256.as_str()).or_default().push(entry);
+    }
+
+    let mut duplicates: Vec<Duplicate> = groups.into_iter()
+        .filter(|(_, group)| group.len() > 1)
+        .map(|(sha256, group)| Duplicate {
+            sha256: sha256.to_string(),
+            size: group[0].size,
+            paths: group.iter().map(|e| e.path.clone()).collect(),
+        })
+        .collect();
+    // 浪费的空间最多的排在前面
+    duplicates.sort_by(|a, b| {
+        let wasted = |d: &Duplicate| d.size * (d.paths.len() as u64 - 1);
+        wasted(b).cmp(&wasted(a))
+    });
+    duplicates
+}
+
+fn growth(previous: &ReportRecord, zip_size: u64, entries: &[EntryInfo]) -> Growth {
+    let before: BTreeMap<&str, u64> = previous.entries.iter().map(|e| (e.path.as_str(), e.compressed)).collect();
+    let after: BTreeMap<&str, u64> = entries.iter().map(|e| (e.path.as_str(), e.compressed)).collect();
+
+    let mut changed: Vec<FileDelta> = before.keys().chain(after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|path| FileDelta {
+            path: path.to_string(),
+            previous: before.get(path).copied().unwrap_or(0),
+            current: after.get(path).copied().unwrap_or(0),
+        })
+        .filter(|d| d.previous != d.current)
+        .collect();
+    changed.sort_by_key(|d| std::cmp::Reverse((d.current as i64 - d.previous as i64).abs()));
+    changed.truncate(TOP_CHANGES);
+
+    Growth {
+        previous_zip_size: previous.zip_size,
+        delta: zip_size as i64 - previous.zip_size as i64,
+        changed,
+    }
+}
+
+fn print_report(report: &SizeReport) {
+    println!("📊 Size report for {}", report.artifact);
+    println!(
+        "   {} files, {} uncompressed -> {} compressed ({}), zip {}",
+        report.files, human_size(report.size), human_size(report.compressed),
+        ratio(report.compressed, report.size), human_size(report.zip_size)
+    );
+
+    println!("   Largest files:");
+    for entry in &report.largest {
+        println!(
+            "     {:>10} -> {:>10} ({:>4})  {}",
+            human_size(entry.size), human_size(entry.compressed), ratio(entry.compressed, entry.size), entry.path
+        );
+    }
+
+    println!("   By directory:");
+    for dir in &report.directories {
+        println!(
+            "     {:>10} -> {:>10}  {:>4} files  {}",
+            human_size(dir.size), human_size(dir.compressed), dir.files, dir.path
+        );
+    }
+
+    if !report.duplicates.is_empty() {
+        println!("   Duplicated content:");
+        for duplicate in &report.duplicates {
+            println!("     {} x {}: {}", duplicate.paths.len(), human_size(duplicate.size), duplicate.paths.join(", "));
+        }
+    }
+
+    if let Some(growth) = &report.growth {
+        let percent = if growth.previous_zip_size > 0 {
+            format!(" ({:+.1}%)", growth.delta as f64 * 100.0 / growth.previous_zip_size as f64)
+        } else {
+            String::new()
+        };
+        println!("   Since last build: {}{}", signed_size(growth.delta), percent);
+        for delta in &growth.changed {
+            let change = match (delta.previous, delta.current) {
+                (0, _) => "added",
+                (_, 0) => "removed",
+                _ => "changed",
+            };
+            println!(
+                "     {:>11}  {} ({})",
+                signed_size(delta.current as i64 - delta.previous as i64), delta.path, change
+            );
+        }
+    }
+
+    if !report.suspicious.is_empty() {
+        println!("⚠️  Possibly unintended files in {}:", report.artifact);
+        for path in &report.suspicious {
+            println!("     {}", path);
+        }
+    }
+}
+
+fn ratio(compressed: u64, size: u64) -> String {
+    format!("{}%", (compressed * 100).checked_div(size).unwrap_or(0))
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn signed_size(delta: i64) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{}", sign, human_size(delta.unsigned_abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::cache::hash_str;
+
+    fn scan(chunks: &[&[u8]]) -> EntryScanner {
+        let mut scanner = EntryScanner::default();
+        for chunk in chunks {
+            scanner.write_all(chunk).unwrap();
+        }
+        scanner
+    }
+
+    #[test]
+    fn finds_debug_section_across_chunks() {
+        assert!(scan(&[b"\x7fEL", b"F....debu", b"g_info..."]).is_unstripped_elf());
+        assert!(scan(&[b"\x7fELF", b".debug_info"]).is_unstripped_elf());
+        assert!(!scan(&[b"\x7fELF....text...."]).is_unstripped_elf());
+        assert!(!scan(&[b"#!/bin/sh\n", b".debug_info"]).is_unstripped_elf());
+    }
+
+    #[test]
+    fn hashes_streamed_content() {
+        let scanner = scan(&[b"hello ", b"world"]);
+        assert_eq!(hex(&scanner.hasher.finalize()), hash_str("hello world"));
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Datelike, Timelike};
+
+use crate::init::source_date_epoch;
+
+/// 未设置 SOURCE_DATE_EPOCH 时使用的固定时间：1980-01-01 00:00:00 UTC（zip 能表示的最早时间）
+pub const DEFAULT_EPOCH: i64 = 315_532_800;
+
+/// 打包产物中所有条目使用的时间戳
+pub fn build_epoch() -> i64 {
+    epoch_or_default(source_date_epoch())
+}
+
+/// SOURCE_DATE_EPOCH 未设置或早于 1980 年时使用 DEFAULT_EPOCH
+fn epoch_or_default(source_date_epoch: Option<i64>) -> i64 {
+    source_date_epoch
+        .unwrap_or(DEFAULT_EPOCH)
+        .max(DEFAULT_EPOCH)
+}
+
+/// 将时间戳转换为 zip 条目时间（DOS 时间精度为 2 秒）
+pub fn zip_datetime(epoch: i64) -> zip::DateTime {
+    DateTime::from_timestamp(epoch, 0)
+        .and_then(|dt| {
+            zip::DateTime::from_date_and_time(
+                dt.year() as u16,
+                dt.month() as u8,
+                dt.day() as u8,
+                dt.hour() as u8,
+                dt.minute() as u8,
+                (dt.second() as u8) & !1,
+            ).ok()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+    use crate::build::cache::BuildCache;
+    use crate::build::{package, sdist};
+    use crate::cmds::ApmmConfig;
+
+    fn project(name: &str) -> PathBuf {
+        let project_dir = std::env::temp_dir().join(format!("apmm-reproducible-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&project_dir);
+        fs::create_dir_all(project_dir.join("system/bin")).unwrap();
+        fs::write(project_dir.join("module.prop"), "id=demo\nname=Demo\nversion=v1.0.0\nversionCode=100\nauthor=me\ndescription=A demo\n").unwrap();
+        fs::write(project_dir.join("customize.sh"), "#!/system/bin/sh\necho hello\n").unwrap();
+        fs::write(project_dir.join("system/bin/tool"), "#!/system/bin/sh\nexit 0\n").unwrap();
+        project_dir
+    }
+
+    /// 修改项目内所有文件的 mtime，模拟在另一台机器上检出
+    fn touch_all(dir: &Path, time: SystemTime) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                touch_all(&path, time);
+            } else {
+                File::options().write(true).open(&path).unwrap().set_modified(time).unwrap();
+            }
+        }
+    }
+
+    fn build(project_dir: &Path) -> (Vec<u8>, Vec<u8>) {
+        let config = ApmmConfig::load_from(project_dir).unwrap();
+        let zips = package::package_module(project_dir, &config, &mut BuildCache::empty(project_dir), None, false).unwrap();
+        let sdist = sdist::build_sdist(project_dir, &config).unwrap();
+        (fs::read(&zips[0]).unwrap(), fs::read(sdist).unwrap())
+    }
+
+    #[test]
+    fn builds_are_byte_identical() {
+        let project_dir = project("twice");
+        let first = build(&project_dir);
+        touch_all(&project_dir.join("system"), SystemTime::now() + Duration::from_secs(3600));
+        File::options().write(true).open(project_dir.join("customize.sh")).unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000)).unwrap();
+        let second = build(&project_dir);
+        assert!(first.0 == second.0, "module zip differs between builds");
+        assert!(first.1 == second.1, "source archive differs between builds");
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn epoch_falls_back_to_1980() {
+        assert_eq!(epoch_or_default(None), DEFAULT_EPOCH);
+        assert_eq!(epoch_or_default(Some(1_700_000_000)), 1_700_000_000);
+        assert_eq!(epoch_or_default(Some(0)), DEFAULT_EPOCH);
+        assert_eq!(zip_datetime(DEFAULT_EPOCH), zip::DateTime::from_date_and_time(1980, 1, 1, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn entry_mtimes_follow_the_epoch() {
+        let project_dir = project("mtime");
+        let files = package::resolve_files(&project_dir, &ApmmConfig::load_from(&project_dir).unwrap().build_config.src).unwrap();
+        let epoch = 1_700_000_001;
+
+        let zip_path = project_dir.join("out.zip");
+        package::zip_dir(&project_dir.join("system"), &zip_path, &[], epoch).unwrap();
+        let mut zip = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        assert!(!zip.is_empty());
+        for i in 0..zip.len() {
+            assert_eq!(zip.by_index(i).unwrap().last_modified(), Some(zip_datetime(epoch)));
+        }
+        // DOS 时间精度为 2 秒
+        assert_eq!(zip_datetime(epoch), zip::DateTime::from_date_and_time(2023, 11, 14, 22, 13, 20).unwrap());
+
+        let tar_path = project_dir.join("out.tar.gz");
+        sdist::write_archive(&tar_path, "demo-v1.0.0", &files, &[], epoch).unwrap();
+        let mut tar = tar::Archive::new(GzDecoder::new(File::open(&tar_path).unwrap()));
+        let mut count = 0;
+        for entry in tar.entries().unwrap() {
+            assert_eq!(entry.unwrap().header().mtime().unwrap(), epoch as u64);
+            count += 1;
+        }
+        assert_eq!(count, files.len());
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+}
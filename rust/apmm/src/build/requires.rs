@@ -0,0 +1,417 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::process::Command;
+
+/// 版本号：数字发布段 + 可选的预发布标记（PEP 440 / semver 两种写法都支持）
+///
+/// - `1.2.3`、`v1.2`
+/// - `1.0.0a1`、`1.0.0b2`、`1.0.0rc1`、`1.0.0.dev3`
+/// - `1.0.0-alpha.1`、`1.0.0-beta`、`1.0.0-rc.2`
+#[derive(Debug, Clone)]
+pub struct Version {
+    release: Vec<u64>,
+    /// (阶段, 序号)：dev < alpha < beta < rc，正式版为 None
+    pre: Option<(u8, u64)>,
+}
+
+impl Version {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let text = input.trim();
+        let text = text.strip_prefix('v').or_else(|| text.strip_prefix('V')).unwrap_or(text);
+        // 忽略构建元数据（semver 的 +build）
+        let text = text.split('+').next().unwrap_or(text);
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut end = 0;
+        while end < chars.len() {
+            let c = chars[end];
+            if c.is_ascii_digit() || (c == '.' && chars.get(end + 1).is_some_and(|n| n.is_ascii_digit())) {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+
+        let release_text: String = chars[..end].iter().collect();
+        if release_text.is_empty() {
+            return Err(format!("Invalid version '{}'", input));
+        }
+        let release = release_text.split('.')
+            .map(|part| part.parse::<u64>().map_err(|_| format!("Invalid version '{}'", input)))
+            .collect::<Result<Vec<u64>, String>>()?;
+
+        let rest: String = chars[end..].iter().collect();
+        let rest = rest.trim_start_matches(['-', '.', '_']);
+        let pre = if rest.is_empty() {
+            None
+        } else {
+            let label: String = rest.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+            let number = rest[label.len()..].trim_start_matches(['-', '.', '_']);
+            let stage = match label.to_ascii_lowercase().as_str() {
+                "dev" => 0,
+                "a" | "alpha" => 1,
+                "b" | "beta" => 2,
+                "c" | "rc" | "pre" | "preview" => 3,
+                _ => return Err(format!("Unsupported pre-release tag in version '{}'", input)),
+            };
+            let number = if number.is_empty() {
+                0
+            } else {
+                number.parse().map_err(|_| format!("Invalid version '{}'", input))?
+            };
+            Some((stage, number))
+        };
+
+        Ok(Version { release, pre })
+    }
+
+    /// 发布段的第 i 位（不足时视为 0）
+    fn part(&self, i: usize) -> u64 {
+        self.release.get(i).copied().unwrap_or(0)
+    }
+
+    /// 构造只有发布段的正式版本
+    fn from_release(release: Vec<u64>) -> Self {
+        Version { release, pre: None }
+    }
+
+    /// 构造某个发布段的最早预发布版本，作为排他上界使用（使 <2.0.0 同时排除 2.0.0 的预发布）
+    fn lower_bound_of(release: Vec<u64>) -> Self {
+        Version { release, pre: Some((0, 0)) }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.release.len().max(other.release.len());
+        for i in 0..len {
+            match self.part(i).cmp(&other.part(i)) {
+                Ordering::Equal => continue,
+                ord => return ord,
+            }
+        }
+        match (&self.pre, &other.pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(b),
+        }
+    }
+}
+
+// 与 Ord 一致：缺少的发布段视为 0，1.0 == 1.0.0
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+    /// PEP 440 `~=`
+    Compatible,
+    /// semver `^`
+    Caret,
+    /// semver `~`
+    Tilde,
+}
+
+#[derive(Debug, Clone)]
+struct Constraint {
+    op: Op,
+    version: Version,
+    /// `==1.2.*` / `!=1.2.*`
+    wildcard: bool,
+}
+
+impl Constraint {
+    fn parse(input: &str) -> Result<Self, String> {
+        let text = input.trim();
+        let ops = [
+            ("~=", Op::Compatible), ("==", Op::Eq), ("!=", Op::Ne), (">=", Op::Ge),
+            ("<=", Op::Le), (">", Op::Gt), ("<", Op::Lt), ("^", Op::Caret), ("~", Op::Tilde),
+            ("=", Op::Eq),
+        ];
+        // 没有运算符时与 Cargo 一致，按 ^ 处理
+        let (op, rest) = ops.iter()
+            .find_map(|(prefix, op)| text.strip_prefix(prefix).map(|rest| (*op, rest)))
+            .unwrap_or((Op::Caret, text));
+
+        let rest = rest.trim();
+        let (version_text, wildcard) = match rest.strip_suffix(".*") {
+            Some(prefix) => (prefix, true),
+            None => (rest, false),
+        };
+        if wildcard && !matches!(op, Op::Eq | Op::Ne) {
+            return Err(format!("Wildcard versions are only allowed with == and != in '{}'", input));
+        }
+        if op == Op::Compatible && version_text.split('.').count() < 2 {
+            return Err(format!("'~=' requires at least two version components in '{}'", input));
+        }
+
+        Ok(Constraint { op, version: Version::parse(version_text)?, wildcard })
+    }
+
+    fn matches(&self, v: &Version) -> bool {
+        let target = &self.version;
+        match self.op {
+            Op::Eq if self.wildcard => prefix_matches(target, v),
+            Op::Ne if self.wildcard => !prefix_matches(target, v),
+            Op::Eq => v == target,
+            Op::Ne => v != target,
+            Op::Ge => v >= target,
+            Op::Gt => v > target,
+            Op::Le => v <= target,
+            Op::Lt => v < target,
+            Op::Compatible => {
+                // ~=1.4.2 等价于 >=1.4.2, ==1.4.*
+                let prefix = Version::from_release(target.release[..target.release.len() - 1].to_vec());
+                v >= target && prefix_matches(&prefix, v)
+            },
+            Op::Caret => {
+                // ^1.2.3 -> <2.0.0，^0.2.3 -> <0.3.0，^0.0.3 -> <0.0.4
+                let index = target.release.iter().position(|&p| p != 0)
+                    .unwrap_or(target.release.len() - 1);
+                v >= target && *v < bump(target, index)
+            },
+            Op::Tilde => {
+                // ~1.2.3 -> <1.3.0，~1 -> <2.0.0
+                let index = if target.release.len() > 1 { 1 } else { 0 };
+                v >= target && *v < bump(target, index)
+            },
+        }
+    }
+}
+
+/// 发布段前缀匹配（用于通配符）
+fn prefix_matches(prefix: &Version, v: &Version) -> bool {
+    (0..prefix.release.len()).all(|i| v.part(i) == prefix.part(i))
+}
+
+/// 第 index 位加一并截断后续位，得到排他上界
+fn bump(v: &Version, index: usize) -> Version {
+    let mut release: Vec<u64> = (0..=index).map(|i| v.part(i)).collect();
+    release[index] += 1;
+    Version::lower_bound_of(release)
+}
+
+/// 单条依赖要求，例如 `apmm>=0.3.0,<1.0` 或 `git >= 2.30`
+#[derive(Debug, Clone)]
+pub struct Requirement {
+    pub name: String,
+    constraints: Vec<Constraint>,
+    text: String,
+}
+
+impl Requirement {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let text = input.trim();
+        let name_end = text.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+            .unwrap_or(text.len());
+        let name = &text[..name_end];
+        if name.is_empty() {
+            return Err(format!("Invalid requirement '{}': missing name", input));
+        }
+        // `apmm0.2` 这样名称后直接跟版本号时无法确定名称在哪里结束
+        if name.as_bytes().windows(2).any(|pair| pair[0] == b'.' && pair[1].is_ascii_digit()) {
+            return Err(format!("Invalid requirement '{}': separate the name and version with a space or operator", input));
+        }
+
+        let spec = text[name_end..].trim();
+        let constraints = if spec.is_empty() {
+            Vec::new()
+        } else {
+            spec.split(',')
+                .map(|c| Constraint::parse(c).map_err(|e| format!("Invalid requirement '{}': {}", input, e)))
+                .collect::<Result<Vec<_>, String>>()?
+        };
+
+        Ok(Requirement { name: name.to_string(), constraints, text: text.to_string() })
+    }
+
+    /// 所有约束都满足时返回 true
+    pub fn matches(&self, version: &Version) -> bool {
+        self.constraints.iter().all(|c| c.matches(version))
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// 检查 [build.system] requires 中的所有要求，一次性报告全部不满足的项
+pub fn check_requirements(requires: &[String]) -> Result<(), String> {
+    let mut problems = Vec::new();
+
+    for entry in requires {
+        let requirement = Requirement::parse(entry)?;
+        let installed = match installed_version(&requirement.name) {
+            Ok(version) => version,
+            Err(e) => {
+                problems.push(format!("{}: {}", requirement, e));
+                continue;
+            },
+        };
+
+        let parsed = Version::parse(&installed)?;
+        if requirement.matches(&parsed) {
+            println!("   ✓ {} (found {})", requirement, installed);
+        } else {
+            problems.push(format!("{}: found {}", requirement, installed));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unsatisfied [build.system] requirements:\n   - {}",
+            problems.join("\n   - ")
+        ))
+    }
+}
+
+/// 获取工具的版本：apmm 使用自身版本，其他工具通过 `<tool> --version` 获取
+fn installed_version(name: &str) -> Result<String, String> {
+    if name.eq_ignore_ascii_case("apmm") {
+        return Ok(env!("CARGO_PKG_VERSION").to_string());
+    }
+
+    let output = Command::new(name)
+        .arg("--version")
+        .output()
+        .map_err(|_| format!("required tool '{}' not found", name))?;
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    extract_version(&text)
+        .ok_or_else(|| format!("could not determine version of '{}' from '{}'", name, text.trim()))
+}
+
+/// 从 `--version` 输出中提取第一个形如版本号的片段
+fn extract_version(text: &str) -> Option<String> {
+    text.split(|c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')'))
+        .map(|token| token.trim_start_matches(['v', 'V']))
+        .find(|token| {
+            token.starts_with(|c: char| c.is_ascii_digit()) && token.contains('.') && Version::parse(token).is_ok()
+        })
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    fn satisfies(requirement: &str, version: &str) -> bool {
+        Requirement::parse(requirement).unwrap().matches(&v(version))
+    }
+
+    #[test]
+    fn missing_release_parts_are_zero() {
+        assert_eq!(v("0.1"), v("0.1.0"));
+        assert_eq!(v("v1"), v("1.0.0"));
+        assert_ne!(v("1.0.0"), v("1.0.0rc1"));
+    }
+
+    #[test]
+    fn pre_releases_sort_before_release() {
+        assert!(v("1.0.0.dev1") < v("1.0.0a1"));
+        assert!(v("1.0.0-alpha.1") < v("1.0.0-beta"));
+        assert!(v("1.0.0b2") < v("1.0.0rc1"));
+        assert!(v("1.0.0-rc.2") < v("1.0.0"));
+        assert_eq!(v("1.0.0+build.5"), v("1.0.0"));
+    }
+
+    #[test]
+    fn equality_operators() {
+        assert!(satisfies("apmm==0.1", "0.1.0"));
+        assert!(satisfies("apmm=0.1.0", "0.1"));
+        assert!(!satisfies("apmm==0.1", "0.1.1"));
+        assert!(satisfies("apmm!=0.1", "0.1.1"));
+        assert!(!satisfies("apmm!=0.1", "0.1.0"));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(satisfies("apmm==1.2.*", "1.2.9"));
+        assert!(!satisfies("apmm==1.2.*", "1.3.0"));
+        assert!(satisfies("apmm!=1.2.*", "1.3.0"));
+        assert!(Requirement::parse("apmm>=1.2.*").is_err());
+    }
+
+    #[test]
+    fn comparison_operators() {
+        assert!(satisfies("apmm>=0.3.0", "0.3"));
+        assert!(!satisfies("apmm>=0.3.0", "0.2.9"));
+        assert!(satisfies("apmm>0.3", "0.3.1"));
+        assert!(!satisfies("apmm>0.3", "0.3.0"));
+        assert!(satisfies("apmm<=1.0", "1.0.0"));
+        assert!(!satisfies("apmm<1.0", "1.0.0"));
+        assert!(satisfies("apmm<1.0", "1.0.0rc1"));
+        assert!(satisfies("apmm >= 0.3, < 1.0", "0.9.9"));
+        assert!(!satisfies("apmm >= 0.3, < 1.0", "1.0.0"));
+    }
+
+    #[test]
+    fn compatible_release() {
+        assert!(satisfies("apmm~=1.4.2", "1.4.5"));
+        assert!(!satisfies("apmm~=1.4.2", "1.5.0"));
+        assert!(satisfies("apmm~=1.4", "1.9"));
+        assert!(!satisfies("apmm~=1.4", "2.0"));
+        assert!(Requirement::parse("apmm~=1").is_err());
+    }
+
+    #[test]
+    fn caret_and_tilde() {
+        assert!(satisfies("apmm^1.2.3", "1.9.0"));
+        assert!(!satisfies("apmm^1.2.3", "2.0.0"));
+        assert!(!satisfies("apmm^1.2.3", "2.0.0-alpha"));
+        assert!(satisfies("apmm^0.2.3", "0.2.9"));
+        assert!(!satisfies("apmm^0.2.3", "0.3.0"));
+        assert!(!satisfies("apmm^0.0.3", "0.0.4"));
+        assert!(satisfies("apmm 0.2", "0.2.5"));
+        assert!(!satisfies("apmm 0.2", "0.3.0"));
+        assert!(!satisfies("apmm 0.2", "0.1.9"));
+        assert!(satisfies("apmm~1.2.3", "1.2.9"));
+        assert!(!satisfies("apmm~1.2.3", "1.3.0"));
+        assert!(satisfies("apmm~1", "1.9"));
+    }
+
+    #[test]
+    fn requirement_parsing() {
+        let requirement = Requirement::parse("git >= 2.30").unwrap();
+        assert_eq!(requirement.name, "git");
+        assert!(requirement.matches(&v("2.43.0")));
+        assert!(Requirement::parse("apmm").unwrap().matches(&v("0.0.1")));
+        assert!(Requirement::parse(">=1.0").is_err());
+        assert!(Requirement::parse("apmm0.2").is_err());
+        assert_eq!(Requirement::parse("python3>=3.8").unwrap().name, "python3");
+        assert!(Requirement::parse("apmm>=abc").is_err());
+        assert!(Version::parse("1.0.0-unknown").is_err());
+    }
+
+    #[test]
+    fn extracts_version_from_tool_output() {
+        assert_eq!(extract_version("git version 2.43.0\n").as_deref(), Some("2.43.0"));
+        assert_eq!(extract_version("zip (v3.0) by Info-ZIP").as_deref(), Some("3.0"));
+        assert_eq!(extract_version("no version here"), None);
+    }
+}
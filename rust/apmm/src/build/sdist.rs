@@ -0,0 +1,59 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+use crate::cmds::ApmmConfig;
+use super::package::{self, BUILD_DIR, DIST_DIR};
+use super::{reproducible, unix};
+
+/// 按 [build.src] 规则生成源码包 build/dist/<id>-<version>-src.tar.gz
+pub fn build_sdist(project_dir: &Path, config: &ApmmConfig) -> Result<PathBuf, String> {
+    let dist_dir = project_dir.join(BUILD_DIR).join(DIST_DIR);
+    fs::create_dir_all(&dist_dir)
+        .map_err(|e| format!("Failed to create {}: {}", dist_dir.display(), e))?;
+
+    let base_name = format!("{}-{}", config.id, config.version);
+    let archive_path = dist_dir.join(format!("{}-src.tar.gz", base_name));
+    println!("📦 Creating source distribution {}...", archive_path.display());
+
+    let files = package::resolve_files(project_dir, &config.build_config.src)?;
+    write_archive(&archive_path, &base_name, &files, &config.build_config.modes, reproducible::build_epoch())?;
+
+    println!("   Archived {} files", files.len());
+    Ok(archive_path)
+}
+
+/// 写入 tar.gz，所有条目放在 base_name/ 下，时间戳统一为 epoch
+pub(super) fn write_archive(archive_path: &Path, base_name: &str, files: &[(PathBuf, String)], modes: &[(String, u32)], epoch: i64) -> Result<(), String> {
+    let file = File::create(archive_path)
+        .map_err(|e| format!("Failed to create {}: {}", archive_path.display(), e))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    // 所有文件放在 <id>-<version>/ 目录下，解压时不会污染当前目录
+    // 头部信息（时间、属主、权限）固定，保证源码包可复现；权限规则与模块 zip 相同
+    let mtime = epoch as u64;
+    for (source, rel_path) in files {
+        let name = format!("{}/{}", base_name, rel_path);
+        let data = File::open(source)
+            .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+        let size = data.metadata()
+            .map_err(|e| format!("Failed to read metadata of {}: {}", source.display(), e))?
+            .len();
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(size);
+        header.set_mode(unix::file_mode(rel_path, modes));
+        header.set_mtime(mtime);
+        header.set_uid(0);
+        header.set_gid(0);
+        tar.append_data(&mut header, &name, data)
+            .map_err(|e| format!("Failed to add {} to source archive: {}", rel_path, e))?;
+    }
+
+    tar.into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| format!("Failed to finish {}: {}", archive_path.display(), e))?;
+    Ok(())
+}
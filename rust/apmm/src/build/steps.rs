@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+use crate::cmds::{ApmmConfig, BuildStep};
+use super::cache::{self, BuildCache};
+use super::package::{BUILD_DIR, DIST_DIR, MODULE_DIR};
+
+/// 构建日志目录（位于 .apmm/ 下）
+pub const LOG_DIR: &str = "logs";
+
+/// 依次执行一组构建步骤，遇到第一个失败的步骤立即停止
+///
+/// 步骤的输入（[build.inputs] 中声明的文件，未声明时为整个项目）和命令都与上次成功执行时一致、
+/// 且上次生成的文件（例如 build/ 下的产物）都还在的步骤会被跳过
+pub fn run_steps(stage: &str, steps: &[BuildStep], project_dir: &Path, config: &ApmmConfig, cache: &mut BuildCache) -> Result<(), String> {
+    for step in steps {
+        let key = format!("{}.{}", stage, step.name);
+        // 在前面的步骤执行之后再计算，前面步骤生成的输入变化时这个步骤也会重新执行
+        let inputs = config.build_config.step_inputs.iter().find(|(name, _)| *name == step.name);
+        let inputs_fingerprint = match inputs {
+            Some((_, patterns)) => cache.inputs_fingerprint(project_dir, &key, patterns)?,
+            None => cache.project_fingerprint(project_dir)?,
+        };
+        let fingerprint = cache::hash_str(&format!("{}\0{}\0{}", key, step.command, inputs_fingerprint));
+        if cache.step_up_to_date(project_dir, &key, &fingerprint) {
+            println!("   ↷ {} (up to date)", step.name);
+            continue;
+        }
+
+        cache.steps.remove(&key);
+        let before = cache::snapshot(project_dir)?;
+        let log_path = run_step(stage, step, project_dir, config)?;
+        let outputs = cache::snapshot(project_dir)?
+            .into_iter()
+            .filter(|(rel_path, stamp)| before.get(rel_path) != Some(stamp))
+            .map(|(rel_path, _)| rel_path)
+            .collect();
+        cache.record_step(project_dir, &key, fingerprint, &log_path, outputs)?;
+    }
+    Ok(())
+}
+
+/// 在项目根目录下通过系统 shell 执行单个构建步骤，输出保存到 .apmm/logs/，返回日志路径
+pub fn run_step(stage: &str, step: &BuildStep, project_dir: &Path, config: &ApmmConfig) -> Result<PathBuf, String> {
+    println!("   ▶ {}: {}", step.name, step.command);
+
+    let log_dir = project_dir.join(".apmm").join(LOG_DIR);
+    fs::create_dir_all(&log_dir)
+        .map_err(|e| format!("Failed to create {}: {}", log_dir.display(), e))?;
+    let log_path = log_dir.join(format!("{}-{}.log", stage, sanitize_name(&step.name)));
+
+    let started = Instant::now();
+    let output = shell_command(&step.command)
+        .current_dir(project_dir)
+        .envs(step_env(stage, project_dir, config))
+        .output()
+        .map_err(|e| format!("Failed to start build step '{}' ({}): {}", step.name, stage, e))?;
+    let elapsed = started.elapsed();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let log = format!(
+        "# stage: {}\n# step: {}\n# command: {}\n# status: {}\n\n--- stdout ---\n{}\n--- stderr ---\n{}",
+        stage, step.name, step.command, output.status, stdout, stderr
+    );
+    fs::write(&log_path, log)
+        .map_err(|e| format!("Failed to write {}: {}", log_path.display(), e))?;
+
+    for line in stdout.lines().chain(stderr.lines()) {
+        println!("     │ {}", line);
+    }
+
+    if !output.status.success() {
+        let reason = match output.status.code() {
+            Some(code) => format!("exit code {}", code),
+            None => "terminated by signal".to_string(),
+        };
+        return Err(format!(
+            "Build step '{}' ({}) failed with {} (log: {})",
+            step.name, stage, reason, log_path.display()
+        ));
+    }
+
+    println!("   ✓ {} ({:.2}s)", step.name, elapsed.as_secs_f64());
+    Ok(log_path)
+}
+
+/// 构建步骤可用的环境变量
+pub fn step_env(stage: &str, project_dir: &Path, config: &ApmmConfig) -> Vec<(String, String)> {
+    let build_dir = project_dir.join(BUILD_DIR);
+    let path = |p: PathBuf| p.to_string_lossy().to_string();
+
+    vec![
+        ("APMM_VERSION".to_string(), config.version.clone()),
+        ("APMM_VERSION_CODE".to_string(), config.version_code.to_string()),
+        ("APMM_MODULE_ID".to_string(), config.id.clone()),
+        ("APMM_MODULE_NAME".to_string(), config.name.clone()),
+        ("APMM_AUTHOR".to_string(), config.author.clone()),
+        ("APMM_STAGE".to_string(), stage.to_string()),
+        ("APMM_PROJECT_DIR".to_string(), path(project_dir.to_path_buf())),
+        ("APMM_BUILD_DIR".to_string(), path(build_dir.clone())),
+        ("APMM_MODULE_DIR".to_string(), path(build_dir.join(MODULE_DIR))),
+        ("APMM_DIST_DIR".to_string(), path(build_dir.join(DIST_DIR))),
+    ]
+}
+
+/// 按平台选择 shell
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// 将步骤名转换为安全的日志文件名
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::Path;
+
+use crate::cmds::ApmmConfig;
+use super::pattern;
+
+/// 暂存时进行模板替换的文件
+pub const TEMPLATE_FILES: &[&str] = &["*.sh", "/webroot/index.html", "/system.prop"];
+
+/// 模板变量：内置的 @MODID@ / @NAME@ / @VERSION@ / @VERSION_CODE@ / @AUTHOR@ / @DESCRIPTION@
+/// 加上 [build.vars] 中的自定义变量（与内置变量同名时内置变量优先）
+pub fn template_vars(config: &ApmmConfig) -> Vec<(String, String)> {
+    let mut vars = vec![
+        ("MODID".to_string(), config.id.clone()),
+        ("NAME".to_string(), config.name.clone()),
+        ("VERSION".to_string(), config.version.clone()),
+        ("VERSION_CODE".to_string(), config.version_code.to_string()),
+        ("AUTHOR".to_string(), config.author.clone()),
+        ("DESCRIPTION".to_string(), config.description.clone()),
+    ];
+    for (key, value) in &config.build_config.vars {
+        if !vars.iter().any(|(name, _)| name == key) {
+            vars.push((key.clone(), value.clone()));
+        }
+    }
+    vars
+}
+
+/// 替换暂存目录中模板文件的占位符，返回实际发生替换的文件数
+pub fn expand_staged(staging_dir: &Path, rel_paths: &[&str], vars: &[(String, String)]) -> Result<usize, String> {
+    let patterns: Vec<String> = TEMPLATE_FILES.iter().map(|p| p.to_string()).collect();
+    let mut expanded = 0;
+
+    for rel_path in rel_paths {
+        if !pattern::matches_any(&patterns, rel_path) {
+            continue;
+        }
+        let path = staging_dir.join(rel_path);
+        let bytes = fs::read(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        // 非 UTF-8 内容不是文本模板，原样保留
+        let Ok(content) = String::from_utf8(bytes) else {
+            continue;
+        };
+
+        let rendered = render(&content, vars);
+        if rendered != content {
+            fs::write(&path, rendered)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            expanded += 1;
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// 把 `@KEY@` 替换为对应的值，未定义的占位符（以及邮箱之类的普通 `@`）保持不变
+pub fn render(content: &str, vars: &[(String, String)]) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find('@') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let replacement = after.find('@').and_then(|end| {
+            let key = &after[..end];
+            vars.iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| (value, end))
+        });
+
+        match replacement {
+            Some((value, end)) => {
+                output.push_str(value);
+                rest = &after[end + 1..];
+            },
+            None => {
+                output.push('@');
+                rest = after;
+            },
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> Vec<(String, String)> {
+        vec![
+            ("MODID".to_string(), "demo".to_string()),
+            ("VERSION".to_string(), "v1.0.0".to_string()),
+        ]
+    }
+
+    #[test]
+    fn renders_known_placeholders() {
+        assert_eq!(render("id=@MODID@ version=@VERSION@", &vars()), "id=demo version=v1.0.0");
+        assert_eq!(render("@MODID@@VERSION@", &vars()), "demov1.0.0");
+    }
+
+    #[test]
+    fn leaves_unknown_placeholders_unchanged() {
+        assert_eq!(render("@UNKNOWN@ @MODID@", &vars()), "@UNKNOWN@ demo");
+        assert_eq!(render("mail me@example.com for @MODID@", &vars()), "mail me@example.com for demo");
+        assert_eq!(render("trailing @", &vars()), "trailing @");
+        assert_eq!(render("@@", &vars()), "@@");
+    }
+
+    #[test]
+    fn expands_only_utf8_template_files() {
+        let staging_dir = std::env::temp_dir().join(format!("apmm-template-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&staging_dir);
+        fs::create_dir_all(staging_dir.join("system/bin")).unwrap();
+        fs::write(staging_dir.join("service.sh"), "echo @MODID@\n").unwrap();
+        fs::write(staging_dir.join("system.prop"), b"ro.demo=@MODID@\xff\n").unwrap();
+        fs::write(staging_dir.join("system/bin/tool"), "@MODID@").unwrap();
+
+        let rel_paths = ["service.sh", "system.prop", "system/bin/tool"];
+        assert_eq!(expand_staged(&staging_dir, &rel_paths, &vars()), Ok(1));
+        assert_eq!(fs::read_to_string(staging_dir.join("service.sh")).unwrap(), "echo demo\n");
+        assert_eq!(fs::read(staging_dir.join("system.prop")).unwrap(), b"ro.demo=@MODID@\xff\n");
+        assert_eq!(fs::read_to_string(staging_dir.join("system/bin/tool")).unwrap(), "@MODID@");
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+}
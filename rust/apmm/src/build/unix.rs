@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::Path;
+
+use super::pattern;
+
+/// 设备上由 sh 解释或逐行解析的文件，暂存时 CRLF 统一转换为 LF
+pub const LF_FILES: &[&str] = &["*.sh", "update-binary", "system.prop", "sepolicy.rule"];
+
+/// 默认可执行（0755）的文件：脚本、update-binary 以及 bin/ 下的原生二进制
+const EXECUTABLE_FILES: &[&str] = &["*.sh", "update-binary", "/bin"];
+/// 可执行文件的权限
+const EXECUTABLE_MODE: u32 = 0o755;
+/// 目录的权限
+pub const DIR_MODE: u32 = 0o755;
+/// 普通文件的权限
+const DEFAULT_MODE: u32 = 0o644;
+
+/// 把暂存目录中需要的文件从 CRLF 转换为 LF，返回转换的文件数
+pub fn normalize_line_endings(staging_dir: &Path, rel_paths: &[&str]) -> Result<usize, String> {
+    let patterns: Vec<String> = LF_FILES.iter().map(|p| p.to_string()).collect();
+    let mut converted = 0;
+
+    for rel_path in rel_paths {
+        if !pattern::matches_any(&patterns, rel_path) {
+            continue;
+        }
+        let path = staging_dir.join(rel_path);
+        let content = fs::read(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if !content.contains(&b'\r') {
+            continue;
+        }
+
+        let mut normalized = Vec::with_capacity(content.len());
+        let mut bytes = content.iter().peekable();
+        while let Some(&byte) = bytes.next() {
+            // 单独的 CR（旧 Mac 换行）同样视为换行
+            if byte == b'\r' {
+                if bytes.peek() != Some(&&b'\n') {
+                    normalized.push(b'\n');
+                }
+                continue;
+            }
+            normalized.push(byte);
+        }
+
+        fs::write(&path, normalized)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        converted += 1;
+    }
+
+    Ok(converted)
+}
+
+/// zip 条目的 Unix 权限：先按默认规则，再按 [build.module.modes] 的声明顺序覆盖（后声明的优先）
+pub fn file_mode(rel_path: &str, modes: &[(String, u32)]) -> u32 {
+    let executable: Vec<String> = EXECUTABLE_FILES.iter().map(|p| p.to_string()).collect();
+    let mut mode = if pattern::matches_any(&executable, rel_path) {
+        EXECUTABLE_MODE
+    } else {
+        DEFAULT_MODE
+    };
+
+    for (glob, custom) in modes {
+        if pattern::matches(glob, rel_path) {
+            mode = *custom;
+        }
+    }
+    mode
+}
+
+/// 解析八进制权限字符串，例如 "0755"、"755"、"0o600"
+pub fn parse_mode(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let digits = value.strip_prefix("0o").unwrap_or(value);
+    u32::from_str_radix(digits, 8).ok().filter(|mode| *mode <= 0o7777)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_crlf_and_lone_cr_in_script_files_only() {
+        let staging_dir = std::env::temp_dir().join(format!("apmm-unix-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&staging_dir);
+        fs::create_dir_all(staging_dir.join("META-INF/com/google/android")).unwrap();
+        fs::create_dir_all(staging_dir.join("system/lib")).unwrap();
+        fs::write(staging_dir.join("customize.sh"), "echo a\r\necho b\recho c\n").unwrap();
+        fs::write(staging_dir.join("META-INF/com/google/android/update-binary"), "#!/sbin/sh\r\n").unwrap();
+        fs::write(staging_dir.join("service.sh"), "echo ok\n").unwrap();
+        fs::write(staging_dir.join("README.md"), "readme\r\n").unwrap();
+        fs::write(staging_dir.join("system/lib/libdemo.so"), b"\x7fELF\r\n\r\x00").unwrap();
+
+        let rel_paths = [
+            "customize.sh",
+            "META-INF/com/google/android/update-binary",
+            "service.sh",
+            "README.md",
+            "system/lib/libdemo.so",
+        ];
+        assert_eq!(normalize_line_endings(&staging_dir, &rel_paths), Ok(2));
+        assert_eq!(fs::read(staging_dir.join("customize.sh")).unwrap(), b"echo a\necho b\necho c\n");
+        assert_eq!(fs::read(staging_dir.join("META-INF/com/google/android/update-binary")).unwrap(), b"#!/sbin/sh\n");
+        assert_eq!(fs::read(staging_dir.join("service.sh")).unwrap(), b"echo ok\n");
+        assert_eq!(fs::read(staging_dir.join("README.md")).unwrap(), b"readme\r\n");
+        assert_eq!(fs::read(staging_dir.join("system/lib/libdemo.so")).unwrap(), b"\x7fELF\r\n\r\x00");
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+
+    #[test]
+    fn default_modes() {
+        assert_eq!(file_mode("customize.sh", &[]), 0o755);
+        assert_eq!(file_mode("META-INF/com/google/android/update-binary", &[]), 0o755);
+        assert_eq!(file_mode("bin/busybox", &[]), 0o755);
+        assert_eq!(file_mode("module.prop", &[]), 0o644);
+    }
+
+    #[test]
+    fn later_modes_take_precedence() {
+        let modes = vec![
+            ("system/bin/*".to_string(), 0o755),
+            ("system/bin/config".to_string(), 0o600),
+            ("*.sh".to_string(), 0o700),
+        ];
+        assert_eq!(file_mode("system/bin/tool", &modes), 0o755);
+        assert_eq!(file_mode("system/bin/config", &modes), 0o600);
+        assert_eq!(file_mode("system/bin/run.sh", &modes), 0o700);
+        assert_eq!(file_mode("service.sh", &modes), 0o700);
+        assert_eq!(file_mode("system/etc/hosts", &modes), 0o644);
+    }
+
+    #[test]
+    fn parses_octal_modes() {
+        assert_eq!(parse_mode("0755"), Some(0o755));
+        assert_eq!(parse_mode(" 600 "), Some(0o600));
+        assert_eq!(parse_mode("0o4755"), Some(0o4755));
+        assert_eq!(parse_mode("789"), None);
+        assert_eq!(parse_mode("17777"), None);
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::cmds::{self, ApmmConfig};
+use crate::sign;
+use super::{package, BuildOptions};
+
+/// 轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 检测到变化后等待文件稳定的时间，避免编辑器多次写入触发多次构建
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// 变化提示中最多列出的文件数
+const MAX_LISTED: usize = 5;
+
+/// 文件快照：源路径 -> (大小, 修改时间)
+type Snapshot = BTreeMap<PathBuf, (u64, Option<SystemTime>)>;
+
+/// 持续监视项目文件，变化时重新构建（可选安装）
+///
+/// 监视范围与 [build.module] 一致：遵循 exclude / extra，始终忽略 build/、.apmm/ 和 .git/，
+/// module.prop 总是被监视。构建失败不会退出，修复后保存即可重新构建
+pub fn watch(project_dir: &Path, options: &BuildOptions) -> Result<String, String> {
+    println!("👀 Watch mode enabled (Ctrl+C to stop)");
+
+    loop {
+        // 构建前拍快照，构建期间保存的修改会触发下一次构建
+        let before = snapshot(project_dir);
+        match super::build_once(project_dir, options) {
+            Ok((_, artifacts)) => {
+                if options.install {
+                    install(&artifacts, options.allow_unsigned);
+                }
+            },
+            Err(e) => println!("❌ Build failed: {}", e),
+        }
+
+        println!("👀 Waiting for changes in {}...", project_dir.display());
+        let after = wait_for_change(project_dir, &before);
+        report_changes(project_dir, &before, &after);
+    }
+}
+
+/// 阻塞直到快照发生变化并稳定下来，返回稳定后的快照
+fn wait_for_change(project_dir: &Path, before: &Snapshot) -> Snapshot {
+    let mut current = loop {
+        thread::sleep(POLL_INTERVAL);
+        let current = snapshot(project_dir);
+        if &current != before {
+            break current;
+        }
+    };
+
+    loop {
+        thread::sleep(DEBOUNCE);
+        let next = snapshot(project_dir);
+        if next == current {
+            return current;
+        }
+        current = next;
+    }
+}
+
+/// 按当前的 module.prop 收集需要监视的文件
+fn snapshot(project_dir: &Path) -> Snapshot {
+    // module.prop 编辑到一半时可能无法解析，此时退回到默认规则
+    let rules = ApmmConfig::load_from(project_dir)
+        .map(|config| config.build_config.module)
+        .unwrap_or_default();
+    let files = package::resolve_files(project_dir, &rules)
+        .or_else(|_| package::collect_files(project_dir, &rules.exclude)
+            .map(|paths| paths.into_iter().map(|p| (project_dir.join(&p), p)).collect()))
+        .unwrap_or_default();
+
+    let mut snapshot = Snapshot::new();
+    let module_prop = project_dir.join("module.prop");
+    for source in files.into_iter().map(|(source, _)| source).chain([module_prop]) {
+        if let Ok(metadata) = fs::metadata(&source) {
+            snapshot.insert(source, (metadata.len(), metadata.modified().ok()));
+        }
+    }
+    snapshot
+}
+
+/// 打印发生变化的文件
+fn report_changes(project_dir: &Path, before: &Snapshot, after: &Snapshot) {
+    let changed: Vec<String> = before.keys()
+        .chain(after.keys().filter(|path| !before.contains_key(*path)))
+        .filter(|path| before.get(*path) != after.get(*path))
+        .map(|path| path.strip_prefix(project_dir).unwrap_or(path).display().to_string())
+        .collect();
+
+    let mut listed = changed.iter().take(MAX_LISTED).cloned().collect::<Vec<_>>().join(", ");
+    if changed.len() > MAX_LISTED {
+        listed.push_str(&format!(" and {} more", changed.len() - MAX_LISTED));
+    }
+    println!();
+    println!("🔄 Changed: {}", listed);
+}
+
+/// 用默认密钥签名新构建的产物后交给 install 命令（--allow-unsigned 时不签名），
+/// 失败时只提示不中断监视
+fn install(artifacts: &[PathBuf], allow_unsigned: bool) {
+    let paths: Vec<String> = artifacts.iter().map(|path| path.to_string_lossy().to_string()).collect();
+    if !allow_unsigned
+        && let Err(e) = sign::cmd_sign(&paths)
+    {
+        println!("❌ Signing failed: {}", e);
+        return;
+    }
+
+    let mut args: Vec<String> = artifacts.iter()
+        .find(|path| path.extension().is_some_and(|ext| ext == "zip"))
+        .map(|path| vec![path.to_string_lossy().to_string()])
+        .unwrap_or_default();
+    if allow_unsigned {
+        args.push("--allow-unsigned".to_string());
+    }
+
+    if let Err(e) = cmds::cmd_install(&args) {
+        println!("❌ Install failed: {}", e);
+    }
+}
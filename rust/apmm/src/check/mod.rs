@@ -0,0 +1,437 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::build::{lint_scripts, validate_props};
+use crate::cmds::ApmmConfig;
+use crate::init::{
+    generate_default_license, generate_update_binary, generate_updater_script, validate_version,
+};
+use crate::prop::{PropDocument, Value, TOP_LEVEL};
+use crate::release::{raw_url, UPDATE_JSON};
+
+/// Magisk 安装器脚本所在目录
+const META_INF_DIR: &str = "META-INF/com/google/android";
+
+/// 问题的严重程度，error 会让 check 失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Warning,
+    Error,
+}
+
+/// 可以自动修复的问题的修复方式
+#[derive(Debug, Clone)]
+enum Fix {
+    /// 修改 module.prop 的顶级键
+    SetProp(&'static str, String),
+    CreateDir(PathBuf),
+    WriteFile(PathBuf, String),
+}
+
+#[derive(Debug, Clone)]
+struct Problem {
+    severity: Severity,
+    /// `文件` 或 `文件:行`
+    location: String,
+    message: String,
+    fix: Option<Fix>,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let icon = match self.severity {
+            Severity::Error => "❌ error",
+            Severity::Warning => "⚠️  warning",
+        };
+        write!(f, "{}: {}: {}", icon, self.location, self.message)?;
+        if self.fix.is_some() {
+            write!(f, " [fixable]")?;
+        }
+        Ok(())
+    }
+}
+
+/// 收集到的问题
+#[derive(Default)]
+struct Report {
+    problems: Vec<Problem>,
+}
+
+impl Report {
+    fn error(&mut self, location: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Error, location.into(), message.into(), None);
+    }
+
+    /// 拆分 `文件:行:列: 信息` 形式的错误
+    fn located_error(&mut self, e: &str) {
+        match e.split_once(": ") {
+            Some((location, message)) if location.rsplitn(3, ':').take(2).all(|n| n.parse::<usize>().is_ok()) => {
+                self.error(location, message);
+            },
+            _ => self.error("module.prop", e),
+        }
+    }
+
+    fn warning(&mut self, location: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Warning, location.into(), message.into(), None);
+    }
+
+    fn fixable(&mut self, severity: Severity, location: impl Into<String>, message: impl Into<String>, fix: Fix) {
+        self.push(severity, location.into(), message.into(), Some(fix));
+    }
+
+    fn push(&mut self, severity: Severity, location: String, message: String, fix: Option<Fix>) {
+        self.problems.push(Problem { severity, location, message, fix });
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.problems.iter().filter(|problem| problem.severity == severity).count()
+    }
+}
+
+/// check 命令处理
+///
+/// 一次列出 module.prop 和项目结构中的所有问题；有 error（或 `--strict` 时有 warning）时返回错误，
+/// 进程退出码为 1，可以直接用作 pre-commit 检查。`--fix` 先修复可自动修复的问题再重新检查
+pub fn cmd_check(args: &[String]) -> Result<String, String> {
+    let mut fix = false;
+    let mut strict = false;
+    for arg in args {
+        match arg.as_str() {
+            "--fix" => fix = true,
+            "--strict" => strict = true,
+            _ => return Err(format!("Unknown check option: {}", arg)),
+        }
+    }
+
+    let project_dir = env::current_dir()
+        .map_err(|e| format!("Failed to get current directory: {}", e))?;
+    if !project_dir.join("module.prop").exists() {
+        return Err("module.prop not found in current directory".to_string());
+    }
+    check(&project_dir, fix, strict)
+}
+
+/// 检查 project_dir 并输出报告
+fn check(project_dir: &Path, fix: bool, strict: bool) -> Result<String, String> {
+    println!("🔍 Checking {}...", project_dir.display());
+    let mut report = check_project(project_dir);
+
+    if fix {
+        let fixes: Vec<Fix> = report.problems.iter().filter_map(|problem| problem.fix.clone()).collect();
+        if !fixes.is_empty() {
+            apply_fixes(project_dir, &fixes)?;
+            println!("🔧 Fixed {} problem(s)", fixes.len());
+            report = check_project(project_dir);
+        }
+    }
+
+    for problem in &report.problems {
+        println!("{}", problem);
+    }
+
+    let errors = report.count(Severity::Error);
+    let warnings = report.count(Severity::Warning);
+    let fixable = report.problems.iter().filter(|problem| problem.fix.is_some()).count();
+    if errors == 0 && warnings == 0 {
+        let success_msg = "No problems found".to_string();
+        println!("✅ {}", success_msg);
+        return Ok(success_msg);
+    }
+
+    let mut summary = format!("{} error(s), {} warning(s)", errors, warnings);
+    if fixable > 0 {
+        summary.push_str(&format!(", {} fixable with apmm check --fix", fixable));
+    }
+    println!("📋 {}", summary);
+
+    if errors > 0 || (strict && warnings > 0) {
+        return Err(format!("Check failed: {}", summary));
+    }
+    Ok(summary)
+}
+
+/// 运行所有检查
+fn check_project(project_dir: &Path) -> Report {
+    let mut report = Report::default();
+
+    // module.prop 语法错误时只能检查项目结构
+    match PropDocument::load(&project_dir.join("module.prop")) {
+        Ok(doc) => match ApmmConfig::load_from(project_dir) {
+            Ok(config) => {
+                check_props(&mut report, &doc, &config);
+                check_paths(&mut report, project_dir, &config);
+                check_update_json(&mut report, project_dir, &config);
+                for finding in lint_scripts(project_dir, &config) {
+                    report.warning(format!("{}:{}", finding.file, finding.line), finding.message);
+                }
+            },
+            Err(e) => report.located_error(&e),
+        },
+        Err(e) => report.located_error(&e),
+    }
+    check_layout(&mut report, project_dir);
+    report
+}
+
+/// 用 init 的校验规则检查模块属性
+fn check_props(report: &mut Report, doc: &PropDocument, config: &ApmmConfig) {
+    let locate = |section: &str, key: &str| match doc.line_of(section, key) {
+        Some(line) => format!("module.prop:{}", line),
+        None => "module.prop".to_string(),
+    };
+    let at = |key: &str| locate(TOP_LEVEL, key);
+
+    // 首尾空白会原样写进 zip 中的 module.prop
+    for key in ["id", "name", "version", "author", "description", "updateJson"] {
+        if let Some(Value::String(value)) = doc.get(TOP_LEVEL, key)
+            && value.trim() != value
+        {
+            report.fixable(Severity::Warning, at(key), format!("{} has leading or trailing whitespace", key), Fix::SetProp(key, value.trim().to_string()));
+        }
+    }
+
+    // 与打包时的校验相同
+    let (version, description, update_json) = (config.version.trim(), config.description.trim(), config.update_json.trim());
+    let errors = validate_props(config).err().unwrap_or_default();
+    for error in &errors {
+        report.error(at(error.key), error.message.clone());
+    }
+    let invalid = |key: &str| errors.iter().any(|error| error.key == key);
+
+    if !invalid("version")
+        && let Err(e) = validate_version(version)
+    {
+        report.warning(at("version"), format!("version '{}' is not like v1.2.3: {}", version, e));
+    }
+    if !invalid("versionCode") && config.version_code > i64::from(i32::MAX) {
+        report.warning(at("versionCode"), format!("versionCode {} exceeds {}, some managers read it as a 32-bit integer", config.version_code, i32::MAX));
+    }
+    if !invalid("description") && description.is_empty() {
+        report.warning(at("description"), "description is empty");
+    }
+
+    let repo = config.github.repo.trim();
+    let repo_valid = matches!(repo.split_once('/'), Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/'));
+    if !repo.is_empty() && !repo_valid {
+        report.error(locate("github", "repo"), format!("github.repo must be owner/name, got '{}'", repo));
+    }
+
+    // 仓库无效时推导出的地址也无效
+    if update_json.is_empty() && repo_valid && config.build_config.variants.is_empty() {
+        let url = raw_url(config, UPDATE_JSON);
+        report.fixable(Severity::Warning, "module.prop", format!("updateJson is not set, managers cannot offer updates (expected {})", url), Fix::SetProp("updateJson", url));
+    }
+}
+
+/// [build] 中引用的文件必须存在
+fn check_paths(report: &mut Report, project_dir: &Path, config: &ApmmConfig) {
+    let build = &config.build_config;
+    let mut referenced: Vec<(String, &String)> = Vec::new();
+    referenced.extend(build.module.extra.iter().map(|path| ("build.module.extra".to_string(), path)));
+    referenced.extend(build.src.extra.iter().map(|path| ("build.src.extra".to_string(), path)));
+    for variant in &build.variants {
+        referenced.extend(variant.rules.extra.iter().map(|path| (format!("build.variants.{}.extra", variant.name), path)));
+        referenced.extend(variant.scripts.iter().map(|(_, source)| (format!("build.variants.{}.scripts", variant.name), source)));
+    }
+    for (abi, paths) in &build.native {
+        referenced.extend(paths.iter().map(|path| (format!("build.native.{}", abi), path)));
+    }
+
+    for (key, path) in referenced {
+        if !project_dir.join(path).exists() {
+            report.error("module.prop", format!("{} refers to '{}', which does not exist", key, path));
+        }
+    }
+}
+
+/// update.json 应该与当前版本一致
+fn check_update_json(report: &mut Report, project_dir: &Path, config: &ApmmConfig) {
+    let Ok(content) = fs::read_to_string(project_dir.join(UPDATE_JSON)) else {
+        return;
+    };
+    let json: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(json) => json,
+        Err(e) => {
+            report.error(format!("{}:{}", UPDATE_JSON, e.line()), format!("invalid JSON: {}", e));
+            return;
+        },
+    };
+    let version = json.get("version").and_then(|v| v.as_str()).unwrap_or_default();
+    let version_code = json.get("versionCode").and_then(|v| v.as_i64()).unwrap_or_default();
+    if version != config.version || version_code != config.version_code {
+        report.warning(UPDATE_JSON, format!(
+            "describes {} ({}) but module.prop is at {} ({}), run apmm release",
+            version, version_code, config.version, config.version_code
+        ));
+    }
+}
+
+/// 模块目录结构
+fn check_layout(report: &mut Report, project_dir: &Path) {
+    if !project_dir.join(".apmm").is_dir() {
+        report.fixable(Severity::Warning, ".apmm", "project metadata directory is missing", Fix::CreateDir(project_dir.join(".apmm")));
+    }
+
+    // 没有安装器脚本的 zip 无法在 recovery 中刷入，部分管理器也会拒绝
+    let installers = [
+        ("update-binary", generate_update_binary()),
+        ("updater-script", generate_updater_script()),
+    ];
+    for (name, content) in installers {
+        let path = Path::new(META_INF_DIR).join(name);
+        if !project_dir.join(&path).is_file() {
+            report.fixable(Severity::Warning, path.display().to_string(), "installer script is missing", Fix::WriteFile(project_dir.join(&path), content));
+        }
+    }
+
+    if !project_dir.join("LICENSE").is_file() {
+        report.fixable(Severity::Warning, "LICENSE", "license file is missing", Fix::WriteFile(project_dir.join("LICENSE"), generate_default_license()));
+    }
+
+    // system.prop 由 resetprop 逐行读取
+    if let Ok(content) = fs::read_to_string(project_dir.join("system.prop")) {
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if !matches!(line.split_once('='), Some((key, _)) if !key.trim().is_empty() && !key.trim().contains(char::is_whitespace)) {
+                report.error(format!("system.prop:{}", index + 1), format!("expected name=value, got '{}'", line));
+            }
+        }
+    }
+
+    let webroot = project_dir.join("webroot");
+    if webroot.is_dir() && !webroot.join("index.html").is_file() {
+        report.warning("webroot", "webroot/ has no index.html, the WebUI will not open");
+    }
+}
+
+fn apply_fixes(project_dir: &Path, fixes: &[Fix]) -> Result<(), String> {
+    let prop_path = project_dir.join("module.prop");
+    let mut doc: Option<PropDocument> = None;
+
+    for fix in fixes {
+        match fix {
+            Fix::SetProp(key, value) => {
+                if doc.is_none() {
+                    doc = Some(PropDocument::load(&prop_path)?);
+                }
+                if let Some(doc) = doc.as_mut() {
+                    doc.set(TOP_LEVEL, key, Value::String(value.clone()));
+                }
+            },
+            Fix::CreateDir(dir) => {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            },
+            Fix::WriteFile(path, content) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                }
+                fs::write(path, content)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            },
+        }
+    }
+
+    if let Some(doc) = doc {
+        doc.save(&prop_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROP: &str = "id=demo\nname=Demo\nversion=v1.0.0\nversionCode=100\nauthor=me\ndescription=A demo\n";
+
+    /// 除 module.prop 外结构完整的项目
+    fn project(name: &str, prop: &str) -> PathBuf {
+        let project_dir = env::temp_dir().join(format!("apmm-check-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&project_dir);
+        fs::create_dir_all(project_dir.join(".apmm")).unwrap();
+        fs::create_dir_all(project_dir.join(META_INF_DIR)).unwrap();
+        fs::write(project_dir.join(META_INF_DIR).join("update-binary"), generate_update_binary()).unwrap();
+        fs::write(project_dir.join(META_INF_DIR).join("updater-script"), generate_updater_script()).unwrap();
+        fs::write(project_dir.join("LICENSE"), "license").unwrap();
+        fs::write(project_dir.join("module.prop"), prop).unwrap();
+        project_dir
+    }
+
+    fn problems(report: &Report, severity: Severity) -> Vec<String> {
+        report.problems.iter()
+            .filter(|problem| problem.severity == severity)
+            .map(|problem| format!("{}: {}", problem.location, problem.message))
+            .collect()
+    }
+
+    #[test]
+    fn clean_project_passes() {
+        let project_dir = project("clean", PROP);
+        assert!(check_project(&project_dir).problems.is_empty());
+        assert_eq!(check(&project_dir, false, true), Ok("No problems found".to_string()));
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn reports_errors_and_warnings_with_locations() {
+        let prop = PROP.replace("id=demo", "id=1demo").replace("description=A demo", "description = \"\"") + "\n[github]\nrepo = \"nobody\"\n";
+        let project_dir = project("severity", &prop);
+        fs::write(project_dir.join("system.prop"), "# props\nro.demo=1\nbroken line\n").unwrap();
+        let report = check_project(&project_dir);
+
+        let errors = problems(&report, Severity::Error);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].starts_with("module.prop:1: "), "{:?}", errors);
+        assert!(errors[1].starts_with("module.prop:9: github.repo must be owner/name"), "{:?}", errors);
+        assert!(errors[2].starts_with("system.prop:3: expected name=value"), "{:?}", errors);
+        assert_eq!(problems(&report, Severity::Warning), vec!["module.prop:6: description is empty"]);
+        assert!(check(&project_dir, false, false).unwrap_err().starts_with("Check failed: 3 error(s), 1 warning(s)"));
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn parse_errors_keep_their_location() {
+        let project_dir = project("parse", &(PROP.to_string() + "versionCode = abc\n"));
+        let errors = problems(&check_project(&project_dir), Severity::Error);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].contains(":7:"), "{:?}", errors);
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn strict_fails_on_warnings() {
+        let project_dir = project("strict", &PROP.replace("description=A demo", "description = \"\""));
+        assert_eq!(check(&project_dir, false, false), Ok("0 error(s), 1 warning(s)".to_string()));
+        assert_eq!(check(&project_dir, false, true), Err("Check failed: 0 error(s), 1 warning(s)".to_string()));
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn fix_repairs_fixable_problems() {
+        let project_dir = project("fix", &(PROP.replace("name=Demo", "name=\" Demo \"") + "\n[github]\nrepo = \"me/demo\"\n"));
+        fs::remove_dir_all(project_dir.join(".apmm")).unwrap();
+        fs::remove_file(project_dir.join("LICENSE")).unwrap();
+        fs::remove_file(project_dir.join(META_INF_DIR).join("update-binary")).unwrap();
+        let before = check_project(&project_dir);
+        assert_eq!(before.problems.iter().filter(|problem| problem.fix.is_some()).count(), 5);
+
+        assert_eq!(check(&project_dir, true, true), Ok("No problems found".to_string()));
+        assert!(project_dir.join(".apmm").is_dir());
+        assert!(project_dir.join("LICENSE").is_file());
+        assert!(project_dir.join(META_INF_DIR).join("update-binary").is_file());
+        let doc = PropDocument::load(&project_dir.join("module.prop")).unwrap();
+        assert_eq!(doc.get(TOP_LEVEL, "name"), Some(&Value::from("Demo")));
+        assert_eq!(
+            doc.get(TOP_LEVEL, "updateJson"),
+            Some(&Value::from("https://raw.githubusercontent.com/me/demo/main/update.json"))
+        );
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+}
@@ -1,5 +1,3 @@
-use std::collections::HashMap;
-use std::fs;
 use std::path::{Path, PathBuf};
 use std::env;
 use std::process::Command;
@@ -9,6 +7,13 @@ use std::io::Write;
 use crate::env::cmd_env;
 use crate::init::cmd_init;
 use crate::sync::cmd_sync;
+use crate::sign::{cmd_sign, cmd_verify, verify_artifact};
+use crate::release::cmd_release;
+use crate::config::cmd_config;
+use crate::schema::cmd_schema;
+use crate::check::cmd_check;
+use crate::workspace::ApmmWorkspace;
+use crate::prop::{self, Entries, ModuleProp, PropDocument};
 
 /// 构建步骤
 #[derive(Debug, Clone)]
@@ -17,9 +22,47 @@ pub struct BuildStep {
     pub command: String,
 }
 
+impl BuildStep {
+    /// [[build.prebuild]] 等数组表中每个键值对是一个步骤
+    pub fn from_tables(tables: Vec<Entries<String>>) -> Vec<BuildStep> {
+        tables.into_iter()
+            .flat_map(|table| table.0)
+            .map(|(name, command)| BuildStep { name, command })
+            .collect()
+    }
+}
+
+/// 文件收集规则（对应 [build.module] 等节区的 extra / exclude）
+#[derive(Debug, Clone, Default)]
+pub struct FileRules {
+    pub extra: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+/// 构建变体（对应 [build.variants.<name>]，例如 magisk / ksu / apatch）
+#[derive(Debug, Clone, Default)]
+pub struct BuildVariant {
+    pub name: String,
+    /// 在 [build.module] 规则基础上追加的 extra / exclude
+    pub rules: FileRules,
+    /// 脚本覆盖：(包内路径, 项目内源文件)，来自 [build.variants.<name>.scripts]
+    pub scripts: Vec<(String, String)>,
+}
+
 /// 构建配置
 #[derive(Debug, Clone)]
 pub struct BuildConfig {
+    pub module: FileRules,
+    pub src: FileRules,
+    pub variants: Vec<BuildVariant>,
+    /// 各 ABI 的原生二进制：(ABI, 文件路径列表)，来自 [build.native]
+    pub native: Vec<(String, Vec<String>)>,
+    /// zip 条目权限覆盖：(glob, 权限)，来自 [build.module.modes]
+    pub modes: Vec<(String, u32)>,
+    /// 自定义模板变量：(名称, 值)，来自 [build.vars]，暂存时替换 @名称@
+    pub vars: Vec<(String, String)>,
+    /// 构建步骤的输入文件：(步骤名, glob 列表)，来自 [build.inputs]，未声明的步骤依赖整个项目
+    pub step_inputs: Vec<(String, Vec<String>)>,
     pub prebuild_steps: Vec<BuildStep>,
     pub build_steps: Vec<BuildStep>,
     pub postbuild_steps: Vec<BuildStep>,
@@ -27,6 +70,27 @@ pub struct BuildConfig {
     pub build_backend: String,
 }
 
+/// GitHub 仓库配置（对应 [github]）
+#[derive(Debug, Clone)]
+pub struct GithubConfig {
+    pub repo: String,
+    pub branch: String,
+    /// 模块在仓库中的路径，"." 表示仓库根目录
+    pub path: String,
+    pub proxy_provider: String,
+}
+
+impl Default for GithubConfig {
+    fn default() -> Self {
+        GithubConfig {
+            repo: String::new(),
+            branch: "main".to_string(),
+            path: ".".to_string(),
+            proxy_provider: String::new(),
+        }
+    }
+}
+
 /// APMM 配置结构
 #[derive(Debug, Clone)]
 pub struct ApmmConfig {
@@ -37,105 +101,96 @@ pub struct ApmmConfig {
     pub version_code: i64,
     pub author: String,
     pub license: String,
+    pub update_json: String,
+    /// [script] 中的自定义命令：(名称, 命令)
+    pub scripts: Vec<(String, String)>,
     pub build_config: BuildConfig,
+    pub github: GithubConfig,
+    /// [workspace] 中声明的成员，本项目是工作区根目录时非空
+    pub workspace_members: Vec<String>,
 }
 
 impl ApmmConfig {
-    /// 从 module.prop 内容解析配置
-    pub fn from_content(content: &str) -> Result<Self, String> {
-        let mut config = HashMap::new();
-        let mut current_section = String::new();
-        let mut current_array_section = String::new();
-        let mut prebuild_steps = Vec::new();
-        let mut build_steps = Vec::new();
-        let mut postbuild_steps = Vec::new();
-        let mut system_requires = Vec::new();
-        let mut build_backend = "apmm".to_string();
-        
-        for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            
-            // 处理节区标题
-            if line.starts_with("[[") && line.ends_with("]]") {
-                current_array_section = line[2..line.len()-2].to_string();
-                continue;
-            } else if line.starts_with('[') && line.ends_with(']') {
-                current_section = line[1..line.len()-1].to_string();
-                current_array_section.clear();
-                continue;
-            }
-            
-            // 处理键值对
-            if let Some((key, value)) = line.split_once('=') {
-                let key = key.trim();
-                let value = value.trim().trim_matches('"');
-                
-                // 处理数组节区中的步骤
-                if !current_array_section.is_empty() {
-                    let step = BuildStep {
-                        name: key.to_string(),
-                        command: value.to_string(),
-                    };
-                    
-                    match current_array_section.as_str() {
-                        "build.prebuild" => prebuild_steps.push(step),
-                        "build.build" => build_steps.push(step),
-                        "build.postbuild" => postbuild_steps.push(step),
-                        _ => {}
-                    }
-                } else if current_section == "build.system" {
-                    // 处理系统构建配置
-                    match key {
-                        "requires" => {
-                            // 简单解析数组格式 ["item1", "item2"]
-                            if value.starts_with('[') && value.ends_with(']') {
-                                let items = value[1..value.len()-1]
-                                    .split(',')
-                                    .map(|s| s.trim().trim_matches('"').to_string())
-                                    .collect();
-                                system_requires = items;
-                            }
-                        },
-                        "build-backend" => {
-                            build_backend = value.to_string();
-                        },
-                        _ => {}
-                    }
-                } else if current_section.is_empty() {
-                    // 处理顶级配置
-                    config.insert(key.to_string(), value.to_string());
-                }
-            }
-        }
-        
+    /// 从 module.prop 文档解析配置，path 仅用于错误信息
+    pub fn from_document(doc: &PropDocument, path: &Path) -> Result<Self, String> {
+        let prop: ModuleProp = prop::from_document(doc)
+            .map_err(|e| format!("{}:{}", path.display(), e))?;
+        let author = prop.require_author()
+            .map_err(|e| format!("{}:{}", path.display(), e))?
+            .to_string();
+        let build = prop.build;
+
+        let variants = build.variants.0.into_iter()
+            .map(|(name, variant)| BuildVariant {
+                name,
+                rules: FileRules { extra: variant.extra, exclude: variant.exclude },
+                scripts: variant.scripts.0,
+            })
+            .collect();
+
         let build_config = BuildConfig {
-            prebuild_steps,
-            build_steps,
-            postbuild_steps,
-            system_requires,
-            build_backend,
+            module: FileRules { extra: build.module.extra, exclude: build.module.exclude },
+            src: FileRules { extra: build.src.extra, exclude: build.src.exclude },
+            variants,
+            native: build.native.0.into_iter().map(|(abi, paths)| (abi, paths.0)).collect(),
+            modes: build.module.modes.0.into_iter().map(|(glob, mode)| (glob, mode.0)).collect(),
+            vars: build.vars.0,
+            step_inputs: build.inputs.0.into_iter().map(|(name, paths)| (name, paths.0)).collect(),
+            prebuild_steps: BuildStep::from_tables(build.prebuild),
+            build_steps: BuildStep::from_tables(build.build),
+            postbuild_steps: BuildStep::from_tables(build.postbuild),
+            system_requires: build.system.requires,
+            build_backend: build.system.build_backend,
+        };
+
+        let github = GithubConfig {
+            repo: prop.github.repo,
+            branch: prop.github.branch,
+            path: prop.github.path,
+            proxy_provider: prop.github.proxy_provider,
         };
-        
+
         Ok(ApmmConfig {
-            id: config.get("id").unwrap_or(&"unknown".to_string()).clone(),
-            name: config.get("name").unwrap_or(&"Unknown".to_string()).clone(),
-            description: config.get("description").unwrap_or(&"".to_string()).clone(),
-            version: config.get("version").unwrap_or(&"0.1.0".to_string()).clone(),
-            version_code: config.get("versionCode").and_then(|s| s.parse().ok()).unwrap_or(1),
-            author: config.get("author").unwrap_or(&"Unknown".to_string()).clone(),
-            license: config.get("license").unwrap_or(&"MIT".to_string()).clone(),
+            id: prop.id,
+            name: prop.name,
+            description: prop.description,
+            version: prop.version,
+            version_code: prop.version_code,
+            author,
+            license: prop.license,
+            update_json: prop.update_json,
+            scripts: prop.script.0,
             build_config,
+            github,
+            workspace_members: prop.workspace.map(|workspace| workspace.members).unwrap_or_default(),
         })
     }
     
-    /// 加载 module.prop 文件
+    /// 加载当前目录的 module.prop 文件
     pub fn load() -> Result<Self, String> {
-        let content = fs::read_to_string("module.prop")
-            .map_err(|e| format!("Failed to read module.prop: {}", e))?;
-        Self::from_content(&content)
+        let project_dir = env::current_dir()
+            .map_err(|e| format!("Failed to get current directory: {}", e))?;
+        Self::load_from(&project_dir)
+    }
+
+    /// 加载项目目录的 module.prop，项目属于工作区时继承工作区的默认值
+    pub fn load_from(project_dir: &Path) -> Result<Self, String> {
+        let path = project_dir.join("module.prop");
+        // 错误信息中尽量使用相对当前目录的路径
+        let display = env::current_dir().ok()
+            .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+            .unwrap_or_else(|| path.clone());
+
+        let mut doc = PropDocument::load(&path)?;
+        let workspace = ApmmWorkspace::for_member(project_dir)?;
+        if let Some(workspace) = &workspace {
+            workspace.inherit(&mut doc, project_dir);
+        }
+        let mut config = Self::from_document(&doc, &display)?;
+        if let Some(workspace) = &workspace {
+            workspace.inherit_steps(&mut config.build_config);
+        }
+        Ok(config)
     }
 }
 
@@ -154,7 +209,31 @@ pub fn show_help() {
     println!("    sync                   Full sync: scan, validate, add/remove projects");
     println!("    sync -U                Sync current project and upgrade version");
     println!("  build        Build the module packages");
-    println!("  install      Install the module");
+    println!("    build                  Stage files into build/module/ and zip to build/dist/");
+    println!("    build --sdist          Also create a source tarball from [build.src]");
+    println!("    build --force          Ignore the incremental build cache in .apmm/");
+    println!("    build --variant <name> Only build the given [build.variants.<name>]");
+    println!("    build --report-json    Also write the size report to build/<zip>.report.json");
+    println!("    build --watch          Rebuild whenever module files change");
+    println!("    build --watch --install [--allow-unsigned]  Also sign and install after each rebuild");
+    println!("    build --workspace      Build every member of apmm-workspace.toml or [workspace]");
+    println!("  config       Read and edit module.prop values by dotted key");
+    println!("    config get <key>       Print a value, e.g. github.repo or version");
+    println!("    config set <key> <value>  Set a value in place (checked against the schema)");
+    println!("    config unset <key>     Remove a key");
+    println!("  check        Lint module.prop and the module layout, exit 1 on errors");
+    println!("    check --fix            Fix what can be fixed automatically, then check again");
+    println!("    check --strict         Also fail on warnings");
+    println!("  schema       Print the JSON Schema of module.prop");
+    println!("    schema --output <file> Write the schema to a file");
+    println!("    schema --editor        Set up Taplo / Even Better TOML completion for module.prop");
+    println!("  release      Write update.json and a CHANGELOG.md entry for this version");
+    println!("  sign         Sign artifacts in build/dist/ (ed25519 + SHA256SUMS)");
+    println!("    sign [--key <name>] [files...]");
+    println!("  verify       Verify artifact signatures against trusted keys");
+    println!("    verify [files...]");
+    println!("  install      Install the module (refuses unsigned zips)");
+    println!("    install [zip] [--allow-unsigned]");
     println!("  remove       Remove the module");
     println!("  info         Show module information");
     println!("  help         Show this help message");
@@ -221,9 +300,34 @@ pub fn cmd_build_legacy() -> Result<String, String> {
 }
 
 /// 安装命令
-pub fn cmd_install() -> Result<String, String> {
+pub fn cmd_install(args: &[String]) -> Result<String, String> {
     println!("📱 Installing APMM module...");
     let config = ApmmConfig::load()?;
+
+    let mut allow_unsigned = false;
+    let mut artifact = None;
+    for arg in args {
+        match arg.as_str() {
+            "--allow-unsigned" => allow_unsigned = true,
+            _ if arg.starts_with('-') => return Err(format!("Unknown install option: {}", arg)),
+            _ => artifact = Some(PathBuf::from(arg)),
+        }
+    }
+    let artifact = artifact.unwrap_or_else(|| {
+        Path::new("build").join("dist").join(format!("{}-{}.zip", config.id, config.version))
+    });
+    if !artifact.exists() {
+        return Err(format!("Module zip not found: {}", artifact.display()));
+    }
+
+    // 拒绝未签名或被篡改的模块
+    if allow_unsigned {
+        println!("⚠️  Skipping signature verification for {}", artifact.display());
+    } else {
+        verify_artifact(&artifact)?;
+        println!("🔏 Signature verified: {}", artifact.display());
+    }
+
     let success_msg = format!("Module {} v{} installed successfully!", config.name, config.version);
     println!("✅ {}", success_msg);
     Ok(success_msg)
@@ -249,6 +353,18 @@ pub fn cmd_info() -> Result<String, String> {
     println!("   Version Code: {}", config.version_code);
     println!("   Author: {}", config.author);
     println!("   License: {}", config.license);
+    if !config.scripts.is_empty() {
+        println!("   Scripts:");
+        for (name, command) in &config.scripts {
+            println!("     {} = {}", name, command);
+        }
+    }
+    if !config.workspace_members.is_empty() {
+        println!("   Workspace members:");
+        for member in &config.workspace_members {
+            println!("     {}", member);
+        }
+    }
     Ok("Module information displayed".to_string())
 }
 
@@ -271,7 +387,25 @@ pub fn handle_command(args: &[String]) -> Result<(), String> {
             crate::build::cmd_build(&args[1..])?;
         },
         "install" => {
-            cmd_install()?;
+            cmd_install(&args[1..])?;
+        },
+        "sign" => {
+            cmd_sign(&args[1..])?;
+        },
+        "release" => {
+            cmd_release(&args[1..])?;
+        },
+        "config" => {
+            cmd_config(&args[1..])?;
+        },
+        "schema" => {
+            cmd_schema(&args[1..])?;
+        },
+        "check" => {
+            cmd_check(&args[1..])?;
+        },
+        "verify" => {
+            cmd_verify(&args[1..])?;
         },
         "remove" => {
             cmd_remove()?;
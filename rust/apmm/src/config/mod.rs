@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use crate::prop::{self, split_dotted, ModuleProp, ParseError, PropDocument, Value, TOP_LEVEL};
+use crate::workspace::ApmmWorkspace;
+
+/// 解析后的点分键
+struct Key {
+    section: String,
+    name: String,
+    /// 各段键名，例如 ["github", "repo"]
+    path: Vec<String>,
+}
+
+/// config 命令处理：按点分键读写 module.prop，例如 `github.repo`、`build.module.exclude`、`version`
+pub fn cmd_config(args: &[String]) -> Result<String, String> {
+    let path = Path::new("module.prop");
+    if !path.exists() {
+        return Err("module.prop not found in current directory".to_string());
+    }
+
+    match (args.first().map(String::as_str), args.get(1..).unwrap_or_default()) {
+        (Some("get"), [key]) => get(path, key),
+        (Some("set"), [key, value]) => set(path, key, value),
+        (Some("unset"), [key]) => unset(path, key),
+        (Some("get" | "unset"), _) => Err(format!("Usage: apmm config {} <key>", args[0])),
+        (Some("set"), _) => Err("Usage: apmm config set <key> <value>".to_string()),
+        (Some(other), _) => Err(format!("Unknown config subcommand: {}", other)),
+        (None, _) => Err("Usage: apmm config <get|set|unset> <key> [value]".to_string()),
+    }
+}
+
+/// 输出值本身（数组按 TOML 写法），便于脚本读取
+fn get(path: &Path, key: &str) -> Result<String, String> {
+    let doc = PropDocument::load(path)?;
+    let resolved = resolve_key(key)?;
+    let value = doc.get(&resolved.section, &resolved.name)
+        .ok_or_else(|| format!("{} is not set in module.prop", key))?
+        .to_string();
+    println!("{}", value);
+    Ok(value)
+}
+
+/// 原地修改或新增，注释和顺序保持不变
+///
+/// 值的类型按 module.prop 的结构决定：`version 2` 写成字符串，`versionCode 2` 写成整数
+fn set(path: &Path, key: &str, value: &str) -> Result<String, String> {
+    let mut doc = PropDocument::load(path)?;
+    let resolved = resolve_key(key)?;
+    let workspace = ApmmWorkspace::for_member(Path::new("."))?;
+    let was_valid = check(&effective(&doc, &workspace)).is_ok();
+
+    let parsed = Value::parse(value);
+    let mut candidates = vec![parsed.clone()];
+    if matches!(parsed, Value::Integer(_) | Value::Boolean(_)) {
+        candidates.push(Value::String(value.to_string()));
+    }
+    // 严格检查与普通检查结果一致时，说明这个值本身的类型是对的
+    let value = candidates.into_iter()
+        .find(|candidate| {
+            let mut trial = doc.clone();
+            trial.set(&resolved.section, &resolved.name, candidate.clone());
+            let trial = effective(&trial, &workspace);
+            let lenient = prop::from_document::<ModuleProp>(&trial).err();
+            let strict = prop::from_document_strict::<ModuleProp>(&trial, &resolved.path).err();
+            lenient == strict
+        })
+        .unwrap_or(parsed);
+
+    doc.set(&resolved.section, &resolved.name, value.clone());
+    validate(&effective(&doc, &workspace), key, was_valid)?;
+    doc.save(path)?;
+
+    let success_msg = format!("Set {} = {}", key, value.to_toml());
+    println!("✅ {}", success_msg);
+    Ok(success_msg)
+}
+
+fn unset(path: &Path, key: &str) -> Result<String, String> {
+    let mut doc = PropDocument::load(path)?;
+    let resolved = resolve_key(key)?;
+    let workspace = ApmmWorkspace::for_member(Path::new("."))?;
+    let was_valid = check(&effective(&doc, &workspace)).is_ok();
+
+    if !doc.remove(&resolved.section, &resolved.name) {
+        return Err(format!("{} is not set in module.prop", key));
+    }
+    validate(&effective(&doc, &workspace), key, was_valid)?;
+    doc.save(path)?;
+
+    let success_msg = format!("Removed {}", key);
+    println!("✅ {}", success_msg);
+    Ok(success_msg)
+}
+
+/// 把点分键拆成（节区, 键），没有点的键属于顶级；含点的段需要加引号，例如 `build.module.modes."bin/*.sh"`
+fn resolve_key(key: &str) -> Result<Key, String> {
+    let path = split_dotted(key).ok_or_else(|| format!("Invalid key: {}", key))?;
+    let (name, parents) = path.split_last().ok_or_else(|| format!("Invalid key: {}", key))?;
+    let section = if parents.is_empty() {
+        TOP_LEVEL.to_string()
+    } else {
+        parents.iter()
+            .map(|segment| if segment.contains('.') { format!("\"{}\"", segment) } else { segment.clone() })
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+    Ok(Key { section, name: name.clone(), path })
+}
+
+/// 校验时使用的文档：工作区成员补上继承的默认值，与构建时看到的内容一致
+fn effective(doc: &PropDocument, workspace: &Option<ApmmWorkspace>) -> PropDocument {
+    let mut doc = doc.clone();
+    if let Some(workspace) = workspace {
+        workspace.inherit(&mut doc, Path::new("."));
+    }
+    doc
+}
+
+/// 按构建时的规则解析：结构正确，且补上继承的值后有 author
+fn check(doc: &PropDocument) -> Result<(), ParseError> {
+    prop::from_document::<ModuleProp>(doc)?.require_author().map(|_| ())
+}
+
+/// 用 module.prop 的结构校验修改后的文档
+///
+/// 修改前文档已经有错误时只给出提示，不阻止用来修复错误的修改
+fn validate(doc: &PropDocument, key: &str, was_valid: bool) -> Result<(), String> {
+    match check(doc) {
+        Ok(_) => Ok(()),
+        Err(e) if was_valid => Err(format!("Cannot change {}: {}", key, e.message)),
+        Err(e) => {
+            println!("⚠️  module.prop still has errors: module.prop:{}", e);
+            Ok(())
+        },
+    }
+}
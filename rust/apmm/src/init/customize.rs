@@ -1,10 +1,11 @@
 /// 生成 customize.sh 脚本内容
-pub fn generate_customize_sh(module_id: &str) -> String {
-    format!(r#"#!/system/bin/sh
+pub fn generate_customize_sh() -> String {
+    r#"#!/system/bin/sh
 
 ui_print "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
-ui_print "   Module: {}"
-ui_print "   Author: APMM Team"
+ui_print "   Module: @MODID@"
+ui_print "   Version: @VERSION@ (@VERSION_CODE@)"
+ui_print "   Author: @AUTHOR@"
 ui_print "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
 
 # 验证安卓版本
@@ -18,15 +19,19 @@ fi
 case "$ARCH" in
     arm)
         ui_print "- Device architecture: $ARCH"
+        ABI=armeabi-v7a
         ;;
     arm64)
         ui_print "- Device architecture: $ARCH"
+        ABI=arm64-v8a
         ;;
     x86)
         ui_print "- Device architecture: $ARCH"
+        ABI=x86
         ;;
     x64)
         ui_print "- Device architecture: $ARCH"
+        ABI=x86_64
         ;;
     *)
         ui_print "! Unsupported architecture: $ARCH"
@@ -37,14 +42,24 @@ esac
 # 安装逻辑
 ui_print "- Installing module files..."
 
+# 选择与设备架构匹配的原生二进制（apmm build 按 [build.native] 打包到 bin/<ABI>/）
+if [ -d "$MODPATH/bin/$ABI" ]; then
+    ui_print "- Installing native binaries for $ABI"
+    mv -f "$MODPATH/bin/$ABI"/* "$MODPATH/bin/" 2>/dev/null
+fi
+for abi in arm64-v8a armeabi-v7a x86 x86_64; do
+    rm -rf "$MODPATH/bin/$abi"
+done
+
 # 设置权限
 ui_print "- Setting permissions..."
 set_perm_recursive $MODPATH 0 0 0755 0644
+[ -d "$MODPATH/bin" ] && set_perm_recursive $MODPATH/bin 0 0 0755 0755
 
 # 安装完成
 ui_print "- Installation completed!"
 ui_print "- Module will be activated after reboot"
 ui_print ""
-"#, module_id)
+"#.to_string()
 }
 
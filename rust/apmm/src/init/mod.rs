@@ -25,6 +25,8 @@ mod meta_inf;
 // 重新导出常用功能
 pub use module::generate_module_prop;
 pub use utils::generate_version_code;
+pub use utils::source_date_epoch;
+pub use utils::{validate_module_props, validate_version, PropError};
 pub use license::generate_default_license;
 pub use customize::generate_customize_sh;
 pub use post_fs_data::generate_post_fs_data_sh;
@@ -128,13 +130,13 @@ pub fn cmd_init(args: &[String]) -> Result<String, String> {
         .map_err(|e| format!("写入README.md失败: {}", e))?;
 
     // 生成并写入customize.sh文件
-    let customize_content = generate_customize_sh(&module_id);
+    let customize_content = generate_customize_sh();
     let customize_path = target_dir.join("customize.sh");
     fs::write(&customize_path, &customize_content)
         .map_err(|e| format!("写入customize.sh失败: {}", e))?;
 
     // 生成并写入post-fs-data.sh文件
-    let post_fs_data_content = generate_post_fs_data_sh(&module_id);
+    let post_fs_data_content = generate_post_fs_data_sh();
     let post_fs_data_path = target_dir.join("post-fs-data.sh");
     fs::write(&post_fs_data_path, &post_fs_data_content)
         .map_err(|e| format!("写入post-fs-data.sh失败: {}", e))?;
@@ -146,7 +148,7 @@ pub fn cmd_init(args: &[String]) -> Result<String, String> {
         .map_err(|e| format!("写入service.sh失败: {}", e))?;
 
     // 生成并写入uninstall.sh文件
-    let uninstall_content = generate_uninstall_sh(&module_id);
+    let uninstall_content = generate_uninstall_sh();
     let uninstall_path = target_dir.join("uninstall.sh");
     fs::write(&uninstall_path, &uninstall_content)
         .map_err(|e| format!("写入uninstall.sh失败: {}", e))?;
@@ -184,7 +186,7 @@ pub fn cmd_init(args: &[String]) -> Result<String, String> {
     let updater_script_path = meta_inf_dir.join("updater-script");
     fs::write(&updater_script_path, &updater_script_content)
         .map_err(|e| format!("写入updater-script失败: {}", e))?;// 生成 webroot 文件夹及其内容
-    generate_webroot_folder(&target_dir, &module_id)
+    generate_webroot_folder(&target_dir)
         .map_err(|e| format!("生成webroot文件夹失败: {}", e))?;
 
     // 将项目添加到元数据
@@ -1,11 +1,12 @@
-/// 生成module.prop内容
-pub fn generate_module_prop(module_id: &str, author: &str, version_code: i64) -> String {
-    format!(r#"id = "{}"
-name = "{}"
+use crate::prop::{PropDocument, Value, TOP_LEVEL};
+
+/// module.prop 模板，id / name / versionCode / author 和 apmm 版本要求由 generate_module_prop 填入
+const MODULE_PROP_TEMPLATE: &str = r#"id = ""
+name = ""
 description = "APMM (Android Patch Module Manager)"
 version = "v0.1.0"
-versionCode = {}
-author = "{}"
+versionCode = 1
+author = ""
 license = "MIT"
 # updateJson = ""
 
@@ -18,6 +19,10 @@ license = "MIT"
 # extra = ["Path/to/extra/file1", "Path/to/extra/file2"]
 exclude = ["src", "rust", ".*", "uv.lock", "dist", "build"]
 
+[build.module.modes]
+# zip 中的文件权限，默认脚本与 bin/ 为 0755，其他为 0644
+# "tools/*" = "0755"
+
 [build.src]
 # extra = ["src/extra1", "src/extra2"]
 exclude = ["src", "rust", ".*", "uv.lock", "dist", "build"]
@@ -35,8 +40,13 @@ step1 = "echo 'Postbuild step 1: cleaning up APMM build'"
 [[build.postbuild]]
 step2 = "echo 'Postbuild step 2: Finalizing APMM build'"
 
+[build.vars]
+# 自定义模板变量：暂存时替换脚本、webroot/index.html、system.prop 中的 @KEY@
+# 内置变量：@MODID@ @NAME@ @VERSION@ @VERSION_CODE@ @AUTHOR@ @DESCRIPTION@
+# GREETING = "hello"
+
 [build.system]
-requires = ["apmm>=0.3.0"]
+requires = ["apmm"]
 build-backend = "apmm"
 
 [github]
@@ -44,6 +54,16 @@ build-backend = "apmm"
 # path = "." # 这个很重要，表示模块是在仓库根目录下
 # branch = "main"
 # proxy-provider = "https://api.akams.cn/github"
-"#, module_id, module_id, version_code, author)
+"#;
+
+/// 生成module.prop内容
+pub fn generate_module_prop(module_id: &str, author: &str, version_code: i64) -> String {
+    let mut doc = PropDocument::parse(MODULE_PROP_TEMPLATE).expect("module.prop template is valid");
+    doc.set(TOP_LEVEL, "id", Value::from(module_id));
+    doc.set(TOP_LEVEL, "name", Value::from(module_id));
+    doc.set(TOP_LEVEL, "versionCode", Value::from(version_code));
+    doc.set(TOP_LEVEL, "author", Value::from(author));
+    doc.set("build.system", "requires", Value::from(vec![format!("apmm>={}", env!("CARGO_PKG_VERSION"))]));
+    doc.to_string()
 }
 
@@ -3,9 +3,10 @@
 pub fn generate_system_prop() -> String {
     r#"# System properties for this Magisk module
 # More info at https://topjohnwu.github.io/Magisk/guides.html
+# apmm build 会替换 @MODID@、@VERSION@ 等占位符
 
 # Example properties (uncomment and modify as needed):
-# ro.my.module.version=1.0
+# ro.my.module.version=@VERSION@
 # persist.my.module.enabled=true
 # debug.my.module.log=false
 
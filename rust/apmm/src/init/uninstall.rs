@@ -1,29 +1,30 @@
 
 
 /// 生成 uninstall.sh 脚本内容（卸载时执行）
-pub fn generate_uninstall_sh(module_id: &str) -> String {
-    format!(r#"#!/system/bin/sh
+pub fn generate_uninstall_sh() -> String {
+    r#"#!/system/bin/sh
 
-# APMM Module: {}
+# APMM Module: @MODID@
 # This script will be executed during module uninstallation
 
+# Module directory
+MODDIR="${0%/*}"
+
 # Log file
-LOG_FILE="/data/adb/modules/{}/uninstall.log"
+LOG_FILE="$MODDIR/uninstall.log"
 
 # Ensure log directory exists
 mkdir -p "$(dirname "$LOG_FILE")"
 
 # Log start
-echo "$(date): Uninstalling module {}" > "$LOG_FILE"
+echo "$(date): Uninstalling module @MODID@" > "$LOG_FILE"
 
 # Add your cleanup logic here
 # Example:
-# rm -rf /data/local/tmp/{}
+# rm -rf /data/local/tmp/@MODID@
 # echo "$(date): Cleaned up temporary files" >> "$LOG_FILE"
 
 # Log completion
-echo "$(date): Module {} uninstalled successfully" >> "$LOG_FILE"
-
-ui_print "Module {} uninstalled successfully!"
-"#, module_id, module_id, module_id, module_id, module_id, module_id)
+echo "$(date): Module @MODID@ uninstalled successfully" >> "$LOG_FILE"
+"#.to_string()
 }
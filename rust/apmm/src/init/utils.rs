@@ -1,8 +1,17 @@
-use chrono::{Datelike, Timelike, Utc};
+use std::fmt;
 
-/// 生成版本代码（基于当前时间）
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+/// 读取 SOURCE_DATE_EPOCH 环境变量（可复现构建约定）
+pub fn source_date_epoch() -> Option<i64> {
+    std::env::var("SOURCE_DATE_EPOCH").ok()?.trim().parse().ok()
+}
+
+/// 生成版本代码（基于当前时间，设置了 SOURCE_DATE_EPOCH 时使用该时间）
 pub fn generate_version_code() -> i64 {
-    let now = Utc::now();
+    let now = source_date_epoch()
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .unwrap_or_else(Utc::now);
     // 格式: YYYYMMDDHHMM
     let version_code = format!("{:04}{:02}{:02}{:02}{:02}", 
         now.year(), now.month(), now.day(), now.hour(), now.minute());
@@ -11,8 +20,6 @@ pub fn generate_version_code() -> i64 {
 
 /// 生成版本代码（基于指定时间）
 pub fn generate_version_code_from_timestamp(timestamp: i64) -> i64 {
-    use chrono::{DateTime, Utc};
-    
     if let Some(dt) = DateTime::from_timestamp(timestamp, 0) {
         let version_code = format!("{:04}{:02}{:02}{:02}{:02}", 
             dt.year(), dt.month(), dt.day(), dt.hour(), dt.minute());
@@ -169,8 +176,6 @@ pub fn current_year() -> i32 {
 
 /// 格式化时间戳为可读格式
 pub fn format_timestamp(timestamp: i64) -> String {
-    use chrono::{DateTime, Utc};
-    
     if let Some(dt) = DateTime::from_timestamp(timestamp, 0) {
         dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
     } else {
@@ -363,9 +368,23 @@ pub fn is_valid_author_name(author: &str) -> bool {
     true
 }
 
+/// 模块属性校验发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropError {
+    /// 出错的 module.prop 键
+    pub key: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for PropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
 /// 验证完整的模块属性
 /// 
-/// 返回验证结果和错误信息
+/// 返回所有不合法的属性，build 和 check 共用
 pub fn validate_module_props(
     id: &str,
     name: &str,
@@ -373,49 +392,63 @@ pub fn validate_module_props(
     version_code: i32,
     author: &str,
     description: &str,
-) -> Result<(), String> {
+    update_json: &str,
+) -> Result<(), Vec<PropError>> {
+    let mut errors = Vec::new();
+    let mut error = |key: &'static str, message: String| errors.push(PropError { key, message });
+
     if !is_valid_module_id(id) {
-        return Err(format!(
+        error("id", format!(
             "无效的模块ID '{}': 必须以字母开头，只能包含字母、数字、点(.)、下划线(_)、连字符(-)",
             id
         ));
     }
     
     if !is_valid_module_name(name) {
-        return Err(format!(
+        error("name", format!(
             "无效的模块名称 '{}': 不能为空，不能包含换行符，长度不能超过100个字符",
             name
         ));
     }
     
     if !is_valid_version_string(version) {
-        return Err(format!(
+        error("version", format!(
             "无效的版本字符串 '{}': 不能为空，不能包含换行符，长度不能超过32个字符",
             version
         ));
     }
     
     if !is_valid_version_code(version_code) {
-        return Err(format!(
+        error("versionCode", format!(
             "无效的版本代码 '{}': 必须是正整数",
             version_code
         ));
     }
     
     if !is_valid_author_name(author) {
-        return Err(format!(
+        error("author", format!(
             "无效的作者名称 '{}': 不能为空，不能包含换行符，长度不能超过100个字符",
             author
         ));
     }
     
     if !is_valid_module_description(description) {
-        return Err(format!(
-            "无效的模块描述: 不能包含换行符，长度不能超过500个字符"
+        error("description", "无效的模块描述: 不能包含换行符，长度不能超过500个字符".to_string());
+    }
+
+    if !update_json.is_empty() && !is_valid_update_json(update_json) {
+        error("updateJson", format!(
+            "无效的更新地址 '{}': 必须是 http(s) URL，不能包含空白字符",
+            update_json
         ));
     }
     
-    Ok(())
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// 验证 updateJson 地址是否有效
+pub fn is_valid_update_json(url: &str) -> bool {
+    (url.starts_with("https://") || url.starts_with("http://")) && !url.contains(char::is_whitespace)
 }
 
 /// 为了向后兼容，保留原函数名但使用新的验证逻辑
@@ -423,3 +456,21 @@ pub fn validate_module_props(
 pub fn is_safe_filename(filename: &str) -> bool {
     is_valid_module_id(filename)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_props() {
+        assert_eq!(validate_module_props("demo", "Demo", "v1.0.0", 100, "me", "", ""), Ok(()));
+        assert_eq!(validate_module_props("demo", "Demo", "v1.0.0", 100, "me", "x", "https://example.com/update.json"), Ok(()));
+    }
+
+    #[test]
+    fn reports_every_invalid_key() {
+        let errors = validate_module_props("1demo", "", "v1", 0, "me", "a\nb", "ftp://example.com").unwrap_err();
+        let keys: Vec<&str> = errors.iter().map(|error| error.key).collect();
+        assert_eq!(keys, ["id", "name", "versionCode", "description", "updateJson"]);
+    }
+}
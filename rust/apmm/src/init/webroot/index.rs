@@ -1,27 +1,27 @@
 /// 生成符合 KernelSU WebUI 规范的 index.html 文件
-pub fn generate_index_html(module_id: &str) -> String {
-    format!(r#"<!DOCTYPE html>
+pub fn generate_index_html() -> String {
+    r#"<!DOCTYPE html>
 <html lang="zh-CN">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>{} 模块控制面板</title>
+    <title>@MODID@ 模块控制面板</title>
     <style>
-        * {{
+        * {
             margin: 0;
             padding: 0;
             box-sizing: border-box;
-        }}
+        }
 
-        body {{
+        body {
             font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
             background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
             min-height: 100vh;
             padding: 20px;
             color: #333;
-        }}
+        }
 
-        .container {{
+        .container {
             max-width: 800px;
             margin: 0 auto;
             background: rgba(255, 255, 255, 0.95);
@@ -29,42 +29,42 @@ pub fn generate_index_html(module_id: &str) -> String {
             box-shadow: 0 20px 40px rgba(0, 0, 0, 0.1);
             overflow: hidden;
             backdrop-filter: blur(10px);
-        }}
+        }
 
-        .header {{
+        .header {
             background: linear-gradient(135deg, #4a9eff 0%, #3d8bfd 100%);
             color: white;
             padding: 30px;
             text-align: center;
-        }}
+        }
 
-        .header h1 {{
+        .header h1 {
             font-size: 2rem;
             margin-bottom: 10px;
             font-weight: 300;
-        }}
+        }
 
-        .header p {{
+        .header p {
             opacity: 0.9;
-        }}
+        }
 
-        .content {{
+        .content {
             padding: 30px;
-        }}
+        }
 
-        .section {{
+        .section {
             margin-bottom: 30px;
-        }}
+        }
 
-        .section h2 {{
+        .section h2 {
             color: #4a9eff;
             font-size: 1.3rem;
             margin-bottom: 15px;
             border-bottom: 2px solid #4a9eff;
             padding-bottom: 8px;
-        }}
+        }
 
-        .status-card {{
+        .status-card {
             display: flex;
             align-items: center;
             gap: 15px;
@@ -73,54 +73,54 @@ pub fn generate_index_html(module_id: &str) -> String {
             border-radius: 10px;
             border: 1px solid #e9ecef;
             margin-bottom: 20px;
-        }}
+        }
 
-        .status-indicator {{
+        .status-indicator {
             width: 20px;
             height: 20px;
             border-radius: 50%;
             background: #28a745;
             box-shadow: 0 0 0 3px rgba(40, 167, 69, 0.3);
             animation: pulse 2s infinite;
-        }}
+        }
 
-        @keyframes pulse {{
-            0% {{ box-shadow: 0 0 0 0 rgba(40, 167, 69, 0.7); }}
-            70% {{ box-shadow: 0 0 0 10px rgba(40, 167, 69, 0); }}
-            100% {{ box-shadow: 0 0 0 0 rgba(40, 167, 69, 0); }}
-        }}
+        @keyframes pulse {
+            0% { box-shadow: 0 0 0 0 rgba(40, 167, 69, 0.7); }
+            70% { box-shadow: 0 0 0 10px rgba(40, 167, 69, 0); }
+            100% { box-shadow: 0 0 0 0 rgba(40, 167, 69, 0); }
+        }
 
-        .info-grid {{
+        .info-grid {
             display: grid;
             grid-template-columns: repeat(auto-fit, minmax(250px, 1fr));
             gap: 15px;
-        }}
+        }
 
-        .info-item {{
+        .info-item {
             padding: 15px;
             background: #f8f9fa;
             border-radius: 8px;
             border: 1px solid #e9ecef;
-        }}
+        }
 
-        .info-item strong {{
+        .info-item strong {
             color: #495057;
             display: block;
             margin-bottom: 5px;
-        }}
+        }
 
-        .info-item span {{
+        .info-item span {
             color: #6c757d;
             font-family: 'Courier New', monospace;
-        }}
+        }
 
-        .actions {{
+        .actions {
             display: grid;
             grid-template-columns: repeat(auto-fit, minmax(200px, 1fr));
             gap: 15px;
-        }}
+        }
 
-        .btn {{
+        .btn {
             padding: 12px 20px;
             background: linear-gradient(135deg, #4a9eff 0%, #3d8bfd 100%);
             color: white;
@@ -129,18 +129,18 @@ pub fn generate_index_html(module_id: &str) -> String {
             cursor: pointer;
             transition: all 0.3s ease;
             font-size: 0.9rem;
-        }}
+        }
 
-        .btn:hover {{
+        .btn:hover {
             transform: translateY(-2px);
             box-shadow: 0 8px 25px rgba(74, 158, 255, 0.4);
-        }}
+        }
 
-        .btn:active {{
+        .btn:active {
             transform: translateY(0);
-        }}
+        }
 
-        .log-output {{
+        .log-output {
             background: #2d3748;
             color: #e2e8f0;
             padding: 15px;
@@ -152,28 +152,28 @@ pub fn generate_index_html(module_id: &str) -> String {
             white-space: pre-wrap;
             margin-top: 15px;
             display: none;
-        }}
+        }
 
-        .footer {{
+        .footer {
             background: #f8f9fa;
             padding: 20px;
             text-align: center;
             color: #6c757d;
             border-top: 1px solid #e9ecef;
-        }}
+        }
 
-        @media (max-width: 768px) {{
-            body {{ padding: 10px; }}
-            .header {{ padding: 20px; }}
-            .content {{ padding: 20px; }}
-            .actions {{ grid-template-columns: 1fr; }}
-        }}
+        @media (max-width: 768px) {
+            body { padding: 10px; }
+            .header { padding: 20px; }
+            .content { padding: 20px; }
+            .actions { grid-template-columns: 1fr; }
+        }
     </style>
 </head>
 <body>
     <div class="container">
         <div class="header">
-            <h1>{} 模块</h1>
+            <h1>@MODID@ 模块</h1>
             <p>KernelSU 模块控制面板</p>
         </div>
         
@@ -191,15 +191,15 @@ pub fn generate_index_html(module_id: &str) -> String {
                 <div class="info-grid">
                     <div class="info-item">
                         <strong>模块ID:</strong>
-                        <span>{}</span>
+                        <span>@MODID@</span>
                     </div>
                     <div class="info-item">
                         <strong>版本:</strong>
-                        <span>v0.1.0</span>
+                        <span>@VERSION@</span>
                     </div>
                     <div class="info-item">
                         <strong>作者:</strong>
-                        <span>APMM Team</span>
+                        <span>@AUTHOR@</span>
                     </div>
                     <div class="info-item">
                         <strong>创建工具:</strong>
@@ -221,44 +221,44 @@ pub fn generate_index_html(module_id: &str) -> String {
         </div>
         
         <div class="footer">
-            <p>&copy; 2024 APMM Team. 使用 Android Package Module Manager 创建</p>
+            <p>&copy; @AUTHOR@. 使用 Android Package Module Manager 创建</p>
         </div>
     </div>
 
     <script type="module">
         // 导入 KernelSU API
-        import {{ exec, toast, fullScreen }} from 'kernelsu';
+        import { exec, toast, fullScreen } from 'kernelsu';
 
         // 全局变量
         let isFullScreen = false;
 
         // 刷新状态
-        window.refreshStatus = async function() {{
-            try {{
+        window.refreshStatus = async function() {
+            try {
                 const statusIndicator = document.getElementById('statusIndicator');
                 const statusText = document.getElementById('statusText');
                 
                 // 检查模块状态
-                const {{ errno, stdout }} = await exec('ls /data/adb/modules/{}');
+                const { errno, stdout } = await exec('ls /data/adb/modules/@MODID@');
                 
-                if (errno === 0) {{
+                if (errno === 0) {
                     statusIndicator.style.background = '#28a745';
                     statusText.textContent = '模块运行中';
                     toast('状态刷新成功');
-                }} else {{
+                } else {
                     statusIndicator.style.background = '#dc3545';
                     statusText.textContent = '模块未运行';
                     toast('模块状态异常');
-                }}
-            }} catch (error) {{
+                }
+            } catch (error) {
                 console.error('刷新状态失败:', error);
                 toast('刷新状态失败');
-            }}
-        }};
+            }
+        };
 
         // 查看系统信息
-        window.viewSystemInfo = async function() {{
-            try {{
+        window.viewSystemInfo = async function() {
+            try {
                 const logOutput = document.getElementById('logOutput');
                 logOutput.style.display = 'block';
                 
@@ -271,74 +271,74 @@ pub fn generate_index_html(module_id: &str) -> String {
                 
                 let output = '=== 系统信息 ===\\n';
                 
-                for (const cmd of commands) {{
-                    const {{ errno, stdout }} = await exec(cmd);
-                    if (errno === 0) {{
-                        output += `$ ${{cmd}}\\n${{stdout}}\\n\\n`;
-                    }}
-                }}
+                for (const cmd of commands) {
+                    const { errno, stdout } = await exec(cmd);
+                    if (errno === 0) {
+                        output += `$ ${cmd}\\n${stdout}\\n\\n`;
+                    }
+                }
                 
                 logOutput.textContent = output;
                 toast('系统信息获取成功');
-            }} catch (error) {{
+            } catch (error) {
                 console.error('获取系统信息失败:', error);
                 toast('获取系统信息失败');
-            }}
-        }};
+            }
+        };
 
         // 运行测试
-        window.runTest = async function() {{
-            try {{
+        window.runTest = async function() {
+            try {
                 const logOutput = document.getElementById('logOutput');
                 logOutput.style.display = 'block';
                 
-                let output = '=== {} 模块测试 ===\\n';
-                output += `开始时间: ${{new Date().toLocaleString()}}\\n\\n`;
+                let output = '=== @MODID@ 模块测试 ===\\n';
+                output += `开始时间: ${new Date().toLocaleString()}\\n\\n`;
                 
                 // 测试模块目录
-                const {{ errno: dirErrno, stdout: dirStdout }} = await exec('ls -la /data/adb/modules/{}');
-                if (dirErrno === 0) {{
+                const { errno: dirErrno, stdout: dirStdout } = await exec('ls -la /data/adb/modules/@MODID@');
+                if (dirErrno === 0) {
                     output += '✅ 模块目录存在\\n';
-                    output += `目录内容:\\n${{dirStdout}}\\n\\n`;
-                }} else {{
+                    output += `目录内容:\\n${dirStdout}\\n\\n`;
+                } else {
                     output += '❌ 模块目录不存在\\n\\n';
-                }}
+                }
                 
                 // 测试模块属性
-                const {{ errno: propErrno, stdout: propStdout }} = await exec('cat /data/adb/modules/{}/module.prop');
-                if (propErrno === 0) {{
+                const { errno: propErrno, stdout: propStdout } = await exec('cat /data/adb/modules/@MODID@/module.prop');
+                if (propErrno === 0) {
                     output += '✅ 模块属性文件存在\\n';
-                    output += `属性内容:\\n${{propStdout}}\\n\\n`;
-                }} else {{
+                    output += `属性内容:\\n${propStdout}\\n\\n`;
+                } else {
                     output += '❌ 模块属性文件不存在\\n\\n';
-                }}
+                }
                 
-                output += `完成时间: ${{new Date().toLocaleString()}}\\n`;
+                output += `完成时间: ${new Date().toLocaleString()}\\n`;
                 
                 logOutput.textContent = output;
                 toast('测试完成');
-            }} catch (error) {{
+            } catch (error) {
                 console.error('运行测试失败:', error);
                 toast('运行测试失败');
-            }}
-        }};
+            }
+        };
 
         // 切换全屏
-        window.toggleFullScreen = function() {{
-            try {{
+        window.toggleFullScreen = function() {
+            try {
                 isFullScreen = !isFullScreen;
                 fullScreen(isFullScreen);
                 toast(isFullScreen ? '已进入全屏' : '已退出全屏');
-            }} catch (error) {{
+            } catch (error) {
                 console.error('切换全屏失败:', error);
                 toast('切换全屏失败');
-            }}
-        }};        // 页面加载完成后初始化
-        document.addEventListener('DOMContentLoaded', function() {{
-            console.log('{} 模块 WebUI 已加载');
-            toast('欢迎使用 {} 模块');
-        }});
+            }
+        };        // 页面加载完成后初始化
+        document.addEventListener('DOMContentLoaded', function() {
+            console.log('@MODID@ 模块 WebUI 已加载');
+            toast('欢迎使用 @MODID@ 模块');
+        });
     </script>
 </body>
-</html>"#, module_id, module_id, module_id, module_id, module_id, module_id, module_id, module_id, module_id)
+</html>"#.to_string()
 }
@@ -8,6 +8,13 @@ mod build;
 mod core;
 mod run;
 mod sync;
+mod sign;
+mod release;
+mod config;
+mod schema;
+mod check;
+mod workspace;
+mod prop;
 
 
 /// CLI 入口函数 - 使用共享的命令处理逻辑
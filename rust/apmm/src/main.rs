@@ -5,6 +5,13 @@ mod cmds;
 mod env;
 mod init;
 mod sync;
+mod sign;
+mod release;
+mod config;
+mod schema;
+mod check;
+mod workspace;
+mod prop;
 mod build;
 
 fn main() {
@@ -0,0 +1,482 @@
+use std::fmt;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::document::{split_dotted, Located, PropDocument};
+use super::interpolate::interpolate;
+use super::value::{ParseError, Value};
+
+/// 行列号（从 1 开始）
+#[derive(Debug, Clone, Copy, Default)]
+struct Location {
+    line: usize,
+    column: usize,
+}
+
+/// 按节区嵌套后的文档树，[build.module] 成为 build 表中的 module 表
+enum Node<'a> {
+    Table(Table<'a>),
+    /// `[[name]]` 数组表，位置为第一个标题
+    Tables(Vec<Table<'a>>, Location),
+    Value(&'a Value, Location),
+}
+
+impl Node<'_> {
+    fn location(&self) -> Location {
+        match self {
+            Node::Table(table) => table.location,
+            Node::Tables(_, location) | Node::Value(_, location) => *location,
+        }
+    }
+}
+
+struct Field<'a> {
+    key: String,
+    location: Location,
+    node: Node<'a>,
+}
+
+struct Table<'a> {
+    fields: Vec<Field<'a>>,
+    location: Location,
+}
+
+impl<'a> Table<'a> {
+    fn new(location: Location) -> Self {
+        Table { fields: Vec::new(), location }
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Node<'a>> {
+        self.fields.iter_mut().find(|field| field.key == key).map(|field| &mut field.node)
+    }
+
+    /// 进入子表，不存在时创建；数组表进入最后一个元素
+    fn child(&mut self, key: &str, location: Location) -> Result<&mut Table<'a>, ParseError> {
+        if self.get_mut(key).is_none() {
+            self.fields.push(Field { key: key.to_string(), location, node: Node::Table(Table::new(location)) });
+        }
+        match self.get_mut(key) {
+            Some(Node::Table(table)) => Ok(table),
+            Some(Node::Tables(tables, _)) => Ok(tables.last_mut().expect("array tables are never empty")),
+            _ => Err(error(location, format!("`{}` is already defined as a value", key))),
+        }
+    }
+}
+
+/// 按结构解析 module.prop 文档
+///
+/// 先展开值中的 `${...}` 引用；错误带有行列号，未知的键会给出最接近的已知键作为建议
+pub fn from_document<T: DeserializeOwned>(doc: &PropDocument) -> Result<T, ParseError> {
+    let doc = interpolate(doc)?;
+    let root = build_tree(&doc)?;
+    T::deserialize(TableDeserializer(&root, None))
+}
+
+/// 与 [`from_document`] 相同，但 path 指向的值不做宽松的类型转换
+/// （整数不能当作字符串，带引号的整数不能当作整数），用于判断新写入的值应该使用哪种类型
+pub fn from_document_strict<T: DeserializeOwned>(doc: &PropDocument, path: &[String]) -> Result<T, ParseError> {
+    let doc = interpolate(doc)?;
+    let root = build_tree(&doc)?;
+    T::deserialize(TableDeserializer(&root, Some(path)))
+}
+
+/// 严格检查的目标：剩余的键路径，空路径表示当前节点，None 表示不在目标路径上
+type Strict<'p> = Option<&'p [String]>;
+
+/// 进入子键后的严格检查目标
+fn descend<'p>(strict: Strict<'p>, key: &str) -> Strict<'p> {
+    match strict {
+        Some([first, rest @ ..]) if first == key => Some(rest),
+        _ => None,
+    }
+}
+
+fn build_tree(doc: &PropDocument) -> Result<Table<'_>, ParseError> {
+    let mut root = Table::new(Location { line: 1, column: 1 });
+    // 当前节区的路径，数组表的路径指向其最后一个元素
+    let mut current: Vec<String> = Vec::new();
+
+    for item in doc.located() {
+        match item {
+            Located::Header { name, array, line } => {
+                let location = Location { line, column: 1 };
+                let path = split_dotted(name).ok_or_else(|| error(location, format!("invalid section name `{}`", name)))?;
+                let (last, parents) = path.split_last().expect("section names are never empty");
+                let mut table = &mut root;
+                for key in parents {
+                    table = table.child(key, location)?;
+                }
+                if !array {
+                    table.child(last, location)?;
+                } else if let Some(node) = table.get_mut(last) {
+                    let Node::Tables(tables, _) = node else {
+                        return Err(error(location, format!("`{}` is already defined as a table", name)));
+                    };
+                    tables.push(Table::new(location));
+                } else {
+                    table.fields.push(Field {
+                        key: last.clone(),
+                        location,
+                        node: Node::Tables(vec![Table::new(location)], location),
+                    });
+                }
+                current = path;
+            },
+            Located::Entry { key, value, line, key_column, column } => {
+                let key_location = Location { line, column: key_column };
+                let mut table = &mut root;
+                for segment in &current {
+                    table = table.child(segment, key_location)?;
+                }
+                if table.get_mut(key).is_some() {
+                    return Err(error(key_location, format!("duplicate key `{}`", key)));
+                }
+                table.fields.push(Field {
+                    key: key.to_string(),
+                    location: key_location,
+                    node: Node::Value(value, Location { line, column }),
+                });
+            },
+        }
+    }
+    Ok(root)
+}
+
+fn error(location: Location, message: String) -> ParseError {
+    ParseError { line: location.line, column: location.column, message }
+}
+
+/// 还没有位置的错误（来自 serde 内部）补上位置
+fn locate(mut e: ParseError, location: Location) -> ParseError {
+    if e.line == 0 {
+        e.line = location.line;
+        e.column = location.column;
+    }
+    e
+}
+
+impl std::error::Error for ParseError {}
+
+impl de::Error for ParseError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ParseError { line: 0, column: 0, message: msg.to_string() }
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        let message = match suggest(field, expected) {
+            Some(candidate) => format!("unknown key `{}`, did you mean `{}`?", field, candidate),
+            None if expected.is_empty() => format!("unknown key `{}`", field),
+            None => format!("unknown key `{}`, expected one of: {}", field, expected.join(", ")),
+        };
+        de::Error::custom(message)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        de::Error::custom(format!("missing required key `{}`", field))
+    }
+}
+
+/// 找出与未知键最接近的已知键（忽略大小写的编辑距离）
+fn suggest(field: &str, expected: &[&'static str]) -> Option<&'static str> {
+    let field = field.to_lowercase();
+    let limit = (field.chars().count() / 3).max(1);
+    expected.iter()
+        .map(|candidate| (edit_distance(&field, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+struct TableDeserializer<'t, 'a, 'p>(&'t Table<'a>, Strict<'p>);
+
+impl<'de> de::Deserializer<'de> for TableDeserializer<'_, '_, '_> {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        let access = TableAccess { fields: self.0.fields.iter(), current: None, strict: self.1 };
+        visitor.visit_map(access).map_err(|e| locate(e, self.0.location))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct TableAccess<'t, 'a, 'p> {
+    fields: std::slice::Iter<'t, Field<'a>>,
+    current: Option<&'t Field<'a>>,
+    strict: Strict<'p>,
+}
+
+impl<'de> MapAccess<'de> for TableAccess<'_, '_, '_> {
+    type Error = ParseError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, ParseError> {
+        let Some(field) = self.fields.next() else {
+            return Ok(None);
+        };
+        self.current = Some(field);
+        seed.deserialize(field.key.as_str().into_deserializer())
+            .map(Some)
+            .map_err(|e| locate(e, field.location))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ParseError> {
+        let field = self.current.take().expect("next_value_seed called before next_key_seed");
+        seed.deserialize(NodeDeserializer(&field.node, descend(self.strict, &field.key))).map_err(|mut e| {
+            if e.line == 0 {
+                e.message = format!("invalid value for `{}`: {}", field.key, e.message);
+            }
+            locate(e, field.node.location())
+        })
+    }
+}
+
+struct NodeDeserializer<'t, 'a, 'p>(&'t Node<'a>, Strict<'p>);
+
+impl<'de> de::Deserializer<'de> for NodeDeserializer<'_, '_, '_> {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.0 {
+            Node::Table(table) => TableDeserializer(table, self.1).deserialize_any(visitor),
+            Node::Tables(tables, _) => visitor.visit_seq(TablesAccess(tables.iter(), self.1)),
+            Node::Value(value, _) => ValueDeserializer(value, self.1 == Some(&[])).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.0 {
+            Node::Value(value, _) => ValueDeserializer(value, self.1 == Some(&[])).deserialize_str(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.0 {
+            Node::Value(value, _) => ValueDeserializer(value, self.1 == Some(&[])).deserialize_i64(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct TablesAccess<'t, 'a, 'p>(std::slice::Iter<'t, Table<'a>>, Strict<'p>);
+
+impl<'de> SeqAccess<'de> for TablesAccess<'_, '_, '_> {
+    type Error = ParseError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ParseError> {
+        match self.0.next() {
+            Some(table) => seed.deserialize(TableDeserializer(table, self.1)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// 第二个字段为 true 时不做宽松的类型转换
+struct ValueDeserializer<'t>(&'t Value, bool);
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.0 {
+            Value::String(s) => visitor.visit_str(s),
+            Value::Integer(n) => visitor.visit_i64(*n),
+            Value::Boolean(b) => visitor.visit_bool(*b),
+            Value::Array(items) => visitor.visit_seq(ValuesAccess(items.iter(), self.1)),
+        }
+    }
+
+    /// Magisk 头部的裸值 `version=2` 会被识别成整数，需要字符串的地方按原文接受
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.0.to_plain_string() {
+            Some(text) if !self.1 => visitor.visit_string(text),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        self.deserialize_str(visitor)
+    }
+
+    /// 兼容 `versionCode = "100"` 这样加了引号的整数
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.0 {
+            Value::String(s) if !self.1 && s.trim().parse::<i64>().is_ok() => {
+                visitor.visit_i64(s.trim().parse().unwrap_or_default())
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct ValuesAccess<'t>(std::slice::Iter<'t, Value>, bool);
+
+impl<'de> SeqAccess<'de> for ValuesAccess<'_> {
+    type Error = ParseError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ParseError> {
+        match self.0.next() {
+            Some(value) => seed.deserialize(ValueDeserializer(value, self.1)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Sample {
+        id: String,
+        name: String,
+        #[serde(rename = "versionCode")]
+        version_code: i64,
+        #[serde(default)]
+        build: Build,
+    }
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Build {
+        #[serde(default)]
+        module: Module,
+        #[serde(default)]
+        prebuild: Vec<BTreeMap<String, String>>,
+    }
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Module {
+        #[serde(default)]
+        exclude: Vec<String>,
+    }
+
+    fn load(content: &str) -> Result<Sample, ParseError> {
+        from_document(&PropDocument::parse(content).unwrap())
+    }
+
+    fn location(error: ParseError) -> (usize, usize, String) {
+        (error.line, error.column, error.message)
+    }
+
+    #[test]
+    fn builds_nested_tables() {
+        let sample = load("id=demo\nname=123\nversionCode=\"7\"\n\n[build.module]\nexclude = [\"*.md\"]\n\n[[build.prebuild]]\na = \"x\"\n[[build.prebuild]]\nb = \"y\"\n").unwrap();
+        assert_eq!(sample, Sample {
+            id: "demo".into(),
+            // Magisk 头部的 name=123 按原文作为字符串，带引号的整数按整数
+            name: "123".into(),
+            version_code: 7,
+            build: Build {
+                module: Module { exclude: vec!["*.md".into()] },
+                prebuild: vec![
+                    BTreeMap::from([("a".to_string(), "x".to_string())]),
+                    BTreeMap::from([("b".to_string(), "y".to_string())]),
+                ],
+            },
+        });
+    }
+
+    #[test]
+    fn strict_mode_rejects_lenient_conversions() {
+        let doc = PropDocument::parse("id=demo\nname=123\nversionCode=\"7\"\n").unwrap();
+        assert!(from_document::<Sample>(&doc).is_ok());
+        assert!(from_document_strict::<Sample>(&doc, &["versionCode".to_string()]).is_err());
+        assert!(from_document_strict::<Sample>(&doc, &["name".to_string()]).is_err());
+        assert!(from_document_strict::<Sample>(&doc, &["id".to_string()]).is_ok());
+    }
+
+    #[test]
+    fn locates_value_errors() {
+        let (line, column, message) = location(load("id=demo\nname=x\nversionCode = abc\n").unwrap_err());
+        assert_eq!((line, column), (3, 15));
+        assert!(message.starts_with("invalid value for `versionCode`"), "{}", message);
+
+        let (line, column, _) = location(load("id=demo\nname=x\nversionCode=1\n[build.module]\nexclude = [[\"a\"]]\n").unwrap_err());
+        assert_eq!((line, column), (5, 11));
+    }
+
+    #[test]
+    fn locates_unknown_and_duplicate_keys() {
+        let (line, column, message) = location(load("id=demo\nnme=x\nversionCode=1\n").unwrap_err());
+        assert_eq!((line, column), (2, 1));
+        assert_eq!(message, "unknown key `nme`, did you mean `name`?");
+
+        let (line, column, message) = location(load("id=demo\nname=x\nversionCode=1\n[build.module]\n  exclude = []\n  exclude = []\n").unwrap_err());
+        assert_eq!((line, column, message.as_str()), (6, 3, "duplicate key `exclude`"));
+    }
+
+    #[test]
+    fn locates_structure_errors() {
+        let (line, _, message) = location(load("id=demo\nname=x\nversionCode=1\n[build]\n[[build]]\n").unwrap_err());
+        assert_eq!((line, message.as_str()), (5, "`build` is already defined as a table"));
+
+        let (line, _, message) = location(load("id=demo\nname=x\nversionCode=1\nbuild=1\n[build.module]\n").unwrap_err());
+        assert_eq!((line, message.as_str()), (5, "`build` is already defined as a value"));
+
+        let (line, column, message) = location(load("id=demo\nversionCode=1\n").unwrap_err());
+        assert_eq!((line, column, message.as_str()), (1, 1, "missing required key `name`"));
+    }
+}
@@ -0,0 +1,626 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use super::value::{is_bare_safe, quote, ParseError, Value, ValueParser};
+
+/// 顶级（Magisk 头部）所在的节区名
+pub const TOP_LEVEL: &str = "";
+
+/// 键值对
+#[derive(Debug, Clone)]
+struct Entry {
+    indent: String,
+    /// 原文中的键（可能带引号）
+    raw_key: String,
+    key: String,
+    /// 键所在的行号和列号（从 1 开始，新插入的键为 0）
+    line: usize,
+    key_column: usize,
+    /// 值开始的列号
+    column: usize,
+    /// 键和值之间的原文，例如 " = " 或 "="
+    separator: String,
+    /// 值的原文，多行数组包含换行
+    raw_value: String,
+    value: Value,
+    /// 值之后的空白和行内注释
+    suffix: String,
+}
+
+impl Entry {
+    fn render(&self) -> String {
+        format!("{}{}{}{}{}", self.indent, self.raw_key, self.separator, self.raw_value, self.suffix)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    /// 空行、注释等原样保留的内容
+    Raw(String),
+    /// `[name]` 或 `[[name]]`
+    Header { name: String, array: bool, raw: String, line: usize },
+    Entry(Entry),
+}
+
+/// 带位置的节区标题或键值对，供按结构解析时定位错误
+pub(super) enum Located<'a> {
+    Header { name: &'a str, array: bool, line: usize },
+    Entry { key: &'a str, value: &'a Value, line: usize, key_column: usize, column: usize },
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    item: Item,
+    /// 行尾换行符（"\n"、"\r\n"，文件最后一行可能为空）
+    eol: String,
+}
+
+/// 键值对在文档中的位置
+struct EntryPos {
+    section: String,
+    array: bool,
+    index: usize,
+}
+
+/// 保留格式的 module.prop 文档
+///
+/// 顶部是 Magisk 的 `key=value` / `key = "value"` 头部，下面是 TOML 节区。
+/// 未修改的行按原文写回，注释、缩进、空行和换行符都保持不变；
+/// 修改的值尽量沿用原来的写法（例如 Magisk 风格的无引号值）
+#[derive(Debug, Clone, Default)]
+pub struct PropDocument {
+    lines: Vec<Line>,
+}
+
+impl PropDocument {
+    /// 解析文档内容
+    pub fn parse(content: &str) -> Result<Self, ParseError> {
+        let physical: Vec<&str> = content.split_inclusive('\n').collect();
+        let mut lines = Vec::new();
+        let mut section = TOP_LEVEL.to_string();
+        let mut index = 0;
+
+        while index < physical.len() {
+            let line_no = index + 1;
+            let (text, eol) = split_eol(physical[index]);
+            index += 1;
+
+            let trimmed = text.trim_start();
+            let item = if trimmed.is_empty() || trimmed.starts_with('#') {
+                Item::Raw(text.to_string())
+            } else if trimmed.starts_with('[') {
+                let (name, array) = parse_header(trimmed)
+                    .ok_or_else(|| error(line_no, text.len() - trimmed.len() + 1, "invalid section header"))?;
+                section = name.clone();
+                Item::Header { name, array, raw: text.to_string(), line: line_no }
+            } else {
+                // 多行数组：把后续行并入同一个逻辑行，直到括号闭合
+                let mut logical = text.to_string();
+                let mut logical_eol = eol.to_string();
+                if let Some((_, value)) = text.split_once('=')
+                    && value.trim_start().starts_with('[')
+                {
+                    while !brackets_closed(&logical) && index < physical.len() {
+                        logical.push_str(&logical_eol);
+                        let (next, next_eol) = split_eol(physical[index]);
+                        logical.push_str(next);
+                        logical_eol = next_eol.to_string();
+                        index += 1;
+                    }
+                }
+                let entry = parse_entry(&logical, line_no, section == TOP_LEVEL)?;
+                lines.push(Line { item: Item::Entry(entry), eol: logical_eol });
+                continue;
+            };
+            lines.push(Line { item, eol: eol.to_string() });
+        }
+
+        Ok(PropDocument { lines })
+    }
+
+    /// 读取并解析文件，错误信息带有 `文件:行:列`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content).map_err(|e| format!("{}:{}", path.display(), e))
+    }
+
+    /// 写回文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_string())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// 读取值，section 为 [`TOP_LEVEL`] 时读取 Magisk 头部
+    pub fn get(&self, section: &str, key: &str) -> Option<&Value> {
+        self.find(section, key).map(|index| &self.entry(index).value)
+    }
+
+    /// 键所在的行号，键不存在或是新插入的键时返回 None
+    pub fn line_of(&self, section: &str, key: &str) -> Option<usize> {
+        self.find(section, key)
+            .map(|index| self.entry(index).line)
+            .filter(|line| *line > 0)
+    }
+
+    /// 是否有 `[section]` 节区
+    pub fn has_section(&self, section: &str) -> bool {
+        self.header_index(section).is_some()
+    }
+
+    /// 删除键值对，返回键是否存在
+    pub fn remove(&mut self, section: &str, key: &str) -> bool {
+        match self.find(section, key) {
+            Some(index) => {
+                self.lines.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// 按出现顺序列出节区标题和键值对
+    pub(super) fn located(&self) -> impl Iterator<Item = Located<'_>> {
+        self.lines.iter().filter_map(|line| match &line.item {
+            Item::Header { name, array, line: header_line, .. } => Some(Located::Header { name, array: *array, line: *header_line }),
+            Item::Entry(entry) => Some(Located::Entry {
+                key: &entry.key,
+                value: &entry.value,
+                line: entry.line,
+                key_column: entry.key_column,
+                column: entry.column,
+            }),
+            Item::Raw(_) => None,
+        })
+    }
+
+    /// 按出现顺序替换值，f 接收值和它的行列号，返回 None 表示保持不变
+    pub(super) fn map_values<E>(&mut self, mut f: impl FnMut(&Value, usize, usize) -> Result<Option<Value>, E>) -> Result<(), E> {
+        for line in &mut self.lines {
+            if let Item::Entry(entry) = &mut line.item
+                && let Some(value) = f(&entry.value, entry.line, entry.column)?
+            {
+                entry.raw_value = render_value(&value, &entry.raw_value);
+                entry.value = value;
+            }
+        }
+        Ok(())
+    }
+
+    /// 设置值：已存在时原地替换（保留缩进、注释和写法），否则追加到节区末尾，节区不存在时新建
+    pub fn set(&mut self, section: &str, key: &str, value: Value) {
+        if let Some(index) = self.find(section, key) {
+            let entry = self.entry_mut(index);
+            entry.raw_value = render_value(&value, &entry.raw_value);
+            entry.value = value;
+            return;
+        }
+
+        let positions = self.positions();
+        let siblings: Vec<&EntryPos> = positions.iter()
+            .filter(|pos| !pos.array && pos.section == section)
+            .collect();
+        // 沿用同节区第一个键的缩进、分隔符和引号风格
+        let (indent, separator, style) = match siblings.first() {
+            Some(pos) => {
+                let sibling = self.entry(pos.index);
+                (sibling.indent.clone(), sibling.separator.clone(), sibling.raw_value.clone())
+            },
+            None => (String::new(), " = ".to_string(), "\"\"".to_string()),
+        };
+        let entry = Entry {
+            indent,
+            raw_key: render_key(key),
+            key: key.to_string(),
+            line: 0,
+            key_column: 0,
+            column: 0,
+            separator,
+            raw_value: render_value(&value, &style),
+            value,
+            suffix: String::new(),
+        };
+
+        let insert_at = match siblings.last() {
+            Some(pos) => Some(pos.index + 1),
+            None if section == TOP_LEVEL => Some(self.first_header().unwrap_or(self.lines.len())),
+            None => self.header_index(section).map(|index| index + 1),
+        };
+        match insert_at {
+            Some(index) => self.insert(index, Item::Entry(entry)),
+            None => {
+                // 新节区与前面的内容之间空一行
+                if self.lines.last().is_some_and(|line| !matches!(&line.item, Item::Raw(raw) if raw.trim().is_empty())) {
+                    self.insert(self.lines.len(), Item::Raw(String::new()));
+                }
+                let header = format!("[{}]", section);
+                self.insert(self.lines.len(), Item::Header { name: section.to_string(), array: false, raw: header, line: 0 });
+                self.insert(self.lines.len(), Item::Entry(entry));
+            },
+        }
+    }
+
+    fn positions(&self) -> Vec<EntryPos> {
+        let mut section = TOP_LEVEL.to_string();
+        let mut array = false;
+        let mut positions = Vec::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            match &line.item {
+                Item::Header { name, array: is_array, .. } => {
+                    section = name.clone();
+                    array = *is_array;
+                },
+                Item::Entry(_) => positions.push(EntryPos { section: section.clone(), array, index }),
+                Item::Raw(_) => {},
+            }
+        }
+        positions
+    }
+
+    fn find(&self, section: &str, key: &str) -> Option<usize> {
+        self.positions().into_iter()
+            .find(|pos| !pos.array && pos.section == section && self.entry(pos.index).key == key)
+            .map(|pos| pos.index)
+    }
+
+    fn entry(&self, index: usize) -> &Entry {
+        match &self.lines[index].item {
+            Item::Entry(entry) => entry,
+            _ => unreachable!("line {} is not an entry", index),
+        }
+    }
+
+    fn entry_mut(&mut self, index: usize) -> &mut Entry {
+        match &mut self.lines[index].item {
+            Item::Entry(entry) => entry,
+            _ => unreachable!("line {} is not an entry", index),
+        }
+    }
+
+    fn first_header(&self) -> Option<usize> {
+        self.lines.iter().position(|line| matches!(line.item, Item::Header { .. }))
+    }
+
+    /// 节区最后一次出现的标题
+    fn header_index(&self, section: &str) -> Option<usize> {
+        self.lines.iter().rposition(|line| {
+            matches!(&line.item, Item::Header { name, array: false, .. } if name == section)
+        })
+    }
+
+    fn insert(&mut self, index: usize, item: Item) {
+        let eol = self.default_eol();
+        // 在没有换行符的最后一行之后插入时先补上换行
+        if index > 0
+            && let Some(previous) = self.lines.get_mut(index - 1)
+            && previous.eol.is_empty()
+        {
+            previous.eol = eol.clone();
+        }
+        self.lines.insert(index, Line { item, eol });
+    }
+
+    fn default_eol(&self) -> String {
+        self.lines.iter()
+            .map(|line| line.eol.as_str())
+            .find(|eol| !eol.is_empty())
+            .unwrap_or("\n")
+            .to_string()
+    }
+}
+
+impl fmt::Display for PropDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            match &line.item {
+                Item::Raw(raw) | Item::Header { raw, .. } => f.write_str(raw)?,
+                Item::Entry(entry) => f.write_str(&entry.render())?,
+            }
+            f.write_str(&line.eol)?;
+        }
+        Ok(())
+    }
+}
+
+/// 拆分 `build.variants."my.variant"` 这样的点分名称
+pub fn split_dotted(name: &str) -> Option<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut rest = name.trim();
+    loop {
+        let (segment, after) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            },
+            None => {
+                let end = rest.find('.').unwrap_or(rest.len());
+                (rest[..end].trim(), &rest[end..])
+            },
+        };
+        if segment.is_empty() {
+            return None;
+        }
+        segments.push(segment.to_string());
+        match after.trim_start().strip_prefix('.') {
+            Some(next) => rest = next.trim_start(),
+            None if after.trim().is_empty() => return Some(segments),
+            None => return None,
+        }
+    }
+}
+
+fn error(line: usize, column: usize, message: &str) -> ParseError {
+    ParseError { line, column, message: message.to_string() }
+}
+
+fn split_eol(line: &str) -> (&str, &str) {
+    if let Some(text) = line.strip_suffix("\r\n") {
+        (text, "\r\n")
+    } else if let Some(text) = line.strip_suffix('\n') {
+        (text, "\n")
+    } else {
+        (line, "")
+    }
+}
+
+/// 解析节区标题，返回（名称，是否数组表）
+fn parse_header(text: &str) -> Option<(String, bool)> {
+    let (inner, rest, array) = if let Some(rest) = text.strip_prefix("[[") {
+        let end = rest.find("]]")?;
+        (&rest[..end], &rest[end + 2..], true)
+    } else {
+        let rest = text.strip_prefix('[')?;
+        let end = rest.find(']')?;
+        (&rest[..end], &rest[end + 1..], false)
+    };
+    let rest = rest.trim();
+    let name = inner.trim();
+    if name.is_empty() || !(rest.is_empty() || rest.starts_with('#')) {
+        return None;
+    }
+    Some((name.to_string(), array))
+}
+
+/// 数组的括号是否已经闭合（忽略字符串和注释中的括号）
+fn brackets_closed(text: &str) -> bool {
+    let Some((_, value)) = text.split_once('=') else {
+        return true;
+    };
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut comment = false;
+    for c in value.chars() {
+        if comment {
+            comment = c != '\n';
+            continue;
+        }
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q || c == '\n' {
+                    quote = None;
+                }
+            },
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '#' => comment = true,
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {},
+            },
+        }
+    }
+    depth <= 0
+}
+
+/// 解析键值对（可能是多行的逻辑行）
+fn parse_entry(text: &str, line_no: usize, top_level: bool) -> Result<Entry, ParseError> {
+    let body = text.trim_start();
+    let indent = &text[..text.len() - body.len()];
+    let key_column = indent.chars().count() + 1;
+    let mut column = key_column;
+
+    // 键：裸键或带引号的键
+    let (key, key_len) = if let Some(rest) = body.strip_prefix('"') {
+        let end = rest.find('"').ok_or_else(|| error(line_no, column, "unterminated quoted key"))?;
+        (rest[..end].to_string(), end + 2)
+    } else {
+        let end = body.find('=').ok_or_else(|| error(line_no, column, "expected 'key = value'"))?;
+        let key = body[..end].trim_end();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(error(line_no, column, "invalid key"));
+        }
+        (key.to_string(), key.len())
+    };
+    let raw_key = &body[..key_len];
+    column += raw_key.chars().count();
+
+    let after_key = &body[key_len..];
+    let value_start = after_key.find(|c: char| c != ' ' && c != '\t' && c != '=')
+        .unwrap_or(after_key.len());
+    let separator = &after_key[..value_start];
+    if separator.matches('=').count() != 1 {
+        return Err(error(line_no, column, "expected '=' after key"));
+    }
+    column += separator.chars().count();
+
+    let value_text = &after_key[value_start..];
+    let (value, consumed) = ValueParser::new(value_text, line_no, column).parse(top_level)?;
+    let value_end = value_text.char_indices().nth(consumed).map(|(i, _)| i).unwrap_or(value_text.len());
+    let raw_value = &value_text[..value_end];
+    let suffix = &value_text[value_end..];
+
+    let rest = suffix.trim_start();
+    if !(rest.is_empty() || rest.starts_with('#')) {
+        // 定位到值之后第一个多余字符（值可能跨越多行）
+        let last_line = raw_value.rsplit('\n').next().unwrap_or(raw_value);
+        let (line, start) = match raw_value.matches('\n').count() {
+            0 => (line_no, column),
+            n => (line_no + n, 1),
+        };
+        let column = start + last_line.chars().count() + (suffix.len() - rest.len());
+        return Err(error(line, column, "unexpected characters after value"));
+    }
+
+    Ok(Entry {
+        indent: indent.to_string(),
+        raw_key: raw_key.to_string(),
+        key,
+        line: line_no,
+        key_column,
+        column,
+        separator: separator.to_string(),
+        raw_value: raw_value.to_string(),
+        value,
+        suffix: suffix.to_string(),
+    })
+}
+
+fn render_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+/// 按原值的写法输出新值：无引号的字符串保持无引号，多行数组保持每行一项
+fn render_value(value: &Value, previous: &str) -> String {
+    match value {
+        Value::String(s) if !previous.starts_with(['"', '\'', '[']) && !previous.is_empty() && is_bare_safe(s) => s.clone(),
+        Value::Array(items) if previous.starts_with('[') && previous.contains('\n') => {
+            let item_indent: String = previous.lines()
+                .nth(1)
+                .map(|line| line.chars().take_while(|c| c.is_whitespace()).collect())
+                .unwrap_or_else(|| "    ".to_string());
+            let eol = if previous.contains("\r\n") { "\r\n" } else { "\n" };
+            let close_indent: String = previous.lines()
+                .last()
+                .map(|line| line.chars().take_while(|c| c.is_whitespace()).collect())
+                .unwrap_or_default();
+            let mut rendered = format!("[{}", eol);
+            for item in items {
+                rendered.push_str(&format!("{}{},{}", item_indent, item.to_toml(), eol));
+            }
+            rendered.push_str(&close_indent);
+            rendered.push(']');
+            rendered
+        },
+        _ => value.to_toml(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+id=demo
+name=My Module # shown in the manager
+versionCode = 100
+
+# build settings
+[build.module]
+exclude = [
+    \"*.md\",  # docs
+    \"tests/*\",
+]
+
+[[build.prebuild]]
+gen = \"echo hi\"
+
+[\"quoted.section\"]
+\"odd key\" = 'x'";
+
+    fn parse(content: &str) -> PropDocument {
+        PropDocument::parse(content).unwrap()
+    }
+
+    #[test]
+    fn round_trips_unchanged_documents() {
+        for content in [SAMPLE, "", "\n", "id=a\r\nname = \"b\"\r\n", "  indented = 1   # comment\n"] {
+            assert_eq!(parse(content).to_string(), content);
+        }
+    }
+
+    #[test]
+    fn reads_values_by_section() {
+        let doc = parse(SAMPLE);
+        assert_eq!(doc.get(TOP_LEVEL, "name"), Some(&Value::from("My Module")));
+        assert_eq!(doc.get(TOP_LEVEL, "versionCode"), Some(&Value::Integer(100)));
+        assert_eq!(doc.get("build.module", "exclude"), Some(&Value::from(vec!["*.md".to_string(), "tests/*".to_string()])));
+        // 节区名按头部写法匹配
+        assert_eq!(doc.get("\"quoted.section\"", "odd key"), Some(&Value::from("x")));
+        // [[数组表]] 中的键不能按节区读取
+        assert_eq!(doc.get("build.prebuild", "gen"), None);
+        assert_eq!(doc.line_of("build.module", "exclude"), Some(7));
+        assert!(doc.has_section("build.module"));
+    }
+
+    #[test]
+    fn set_keeps_the_existing_style() {
+        let mut doc = parse(SAMPLE);
+        doc.set(TOP_LEVEL, "name", Value::from("Other"));
+        doc.set(TOP_LEVEL, "versionCode", Value::Integer(101));
+        doc.set("build.module", "exclude", Value::from(vec!["*.txt".to_string()]));
+        let content = doc.to_string();
+        assert!(content.contains("\nname=Other # shown in the manager\n"), "{}", content);
+        assert!(content.contains("\nversionCode = 101\n"), "{}", content);
+        assert!(content.contains("exclude = [\n    \"*.txt\",\n]\n"), "{}", content);
+        assert_eq!(parse(&content).get(TOP_LEVEL, "name"), Some(&Value::from("Other")));
+    }
+
+    #[test]
+    fn set_inserts_new_keys_and_sections() {
+        let mut doc = parse("id=demo\n\n[build]\nbuild-backend = \"apmm\"\n");
+        doc.set(TOP_LEVEL, "author", Value::from("Me"));
+        doc.set("build", "extra", Value::from("x"));
+        doc.set("github", "repo", Value::from("me/demo"));
+        assert_eq!(
+            doc.to_string(),
+            "id=demo\nauthor=Me\n\n[build]\nbuild-backend = \"apmm\"\nextra = \"x\"\n\n[github]\nrepo = \"me/demo\"\n"
+        );
+        assert_eq!(doc.line_of("github", "repo"), None);
+
+        let mut doc = parse("id=a");
+        doc.set(TOP_LEVEL, "name", Value::from("B"));
+        assert_eq!(doc.to_string(), "id=a\nname=B\n");
+    }
+
+    #[test]
+    fn removes_keys() {
+        let mut doc = parse(SAMPLE);
+        assert!(doc.remove(TOP_LEVEL, "versionCode"));
+        assert!(!doc.remove(TOP_LEVEL, "versionCode"));
+        assert!(!doc.to_string().contains("versionCode"));
+    }
+
+    #[test]
+    fn reports_error_locations() {
+        let cases = [
+            ("id=a\n  [build\n", 2, 3, "invalid section header"),
+            ("id=a\nname\n", 2, 1, "expected 'key = value'"),
+            ("id=a\n  bad key = 1\n", 2, 3, "invalid key"),
+            ("[build]\nx = \"a\" b\n", 2, 9, "unexpected characters after value"),
+            ("[build]\nx = [\n  1,\n] y\n", 4, 3, "unexpected characters after value"),
+            ("[build]\nx = \"open\n", 2, 5, "unterminated string"),
+        ];
+        for (content, line, column, message) in cases {
+            let error = PropDocument::parse(content).unwrap_err();
+            assert_eq!((error.line, error.column, error.message.as_str()), (line, column, message), "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn splits_dotted_names() {
+        assert_eq!(split_dotted("build.variants.\"my.variant\""), Some(vec!["build".into(), "variants".into(), "my.variant".into()]));
+        assert_eq!(split_dotted(" a . b "), Some(vec!["a".into(), "b".into()]));
+        assert_eq!(split_dotted("a..b"), None);
+        assert_eq!(split_dotted(""), None);
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::env;
+
+use super::document::{split_dotted, Located, PropDocument};
+use super::schema::default_value;
+use super::value::{ParseError, Value};
+
+/// 展开所有字符串值中的引用，返回展开后的文档副本
+///
+/// `${env:VAR}` 读取环境变量，`${version}`、`${github.repo}` 等读取 module.prop 中的其他键
+/// （被引用的值本身也会展开，循环引用会报错）；`$${` 写出字面的 `${`。
+/// 文档中不存在且没有默认值的名字（如 `${HOME}`）以及 `${0%/*}`、`${VAR:-x}` 这类写法按原文保留，
+/// 方便在构建步骤中写 shell
+pub fn interpolate(doc: &PropDocument) -> Result<PropDocument, ParseError> {
+    let mut resolver = Resolver::new(doc);
+    let mut resolved = doc.clone();
+    resolved.map_values(|value, line, column| resolver.resolve_value(value, line, column))?;
+    Ok(resolved)
+}
+
+struct Resolver<'a> {
+    /// 可以被引用的键：点分路径 -> (值, 行, 列)，[[数组表]] 中的键不能被引用
+    keys: HashMap<Vec<String>, (&'a Value, usize, usize)>,
+    /// 已展开的键
+    resolved: HashMap<Vec<String>, String>,
+    /// 正在展开的键，用于检测循环引用
+    stack: Vec<Vec<String>>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(doc: &'a PropDocument) -> Self {
+        let mut keys = HashMap::new();
+        let mut section = Some(Vec::new());
+        for item in doc.located() {
+            match item {
+                Located::Header { name, array, .. } => {
+                    section = if array { None } else { split_dotted(name) };
+                },
+                Located::Entry { key, value, line, column, .. } => {
+                    if let Some(section) = &section {
+                        let mut path = section.clone();
+                        path.push(key.to_string());
+                        keys.entry(path).or_insert((value, line, column));
+                    }
+                },
+            }
+        }
+        Resolver { keys, resolved: HashMap::new(), stack: Vec::new() }
+    }
+
+    /// 展开值中的引用，没有引用时返回 None
+    fn resolve_value(&mut self, value: &Value, line: usize, column: usize) -> Result<Option<Value>, ParseError> {
+        match value {
+            Value::String(text) if text.contains('$') => {
+                let expanded = self.expand(text, line, column)?;
+                Ok((expanded != *text).then_some(Value::String(expanded)))
+            },
+            Value::Array(items) => {
+                let mut changed = false;
+                let mut resolved = Vec::with_capacity(items.len());
+                for item in items {
+                    match self.resolve_value(item, line, column)? {
+                        Some(item) => {
+                            changed = true;
+                            resolved.push(item);
+                        },
+                        None => resolved.push(item.clone()),
+                    }
+                }
+                Ok(changed.then_some(Value::Array(resolved)))
+            },
+            _ => Ok(None),
+        }
+    }
+
+    fn expand(&mut self, text: &str, line: usize, column: usize) -> Result<String, ParseError> {
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            if let Some(after) = rest.strip_prefix("$${") {
+                expanded.push_str("${");
+                rest = after;
+                continue;
+            }
+            let Some((name, after)) = rest.strip_prefix("${").and_then(|body| body.split_once('}')) else {
+                expanded.push('$');
+                rest = &rest[1..];
+                continue;
+            };
+            match self.reference(name, line, column)? {
+                Some(value) => {
+                    expanded.push_str(&value);
+                    rest = after;
+                },
+                None => {
+                    expanded.push_str("${");
+                    rest = &rest[2..];
+                },
+            }
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+
+    /// 解析 `${name}` 中的 name，不是引用写法时返回 None
+    fn reference(&mut self, name: &str, line: usize, column: usize) -> Result<Option<String>, ParseError> {
+        if let Some(var) = name.strip_prefix("env:") {
+            if !is_identifier(var) {
+                return Err(error(line, column, format!("invalid environment variable name in `${{{}}}`", name)));
+            }
+            return env::var(var).map(Some).map_err(|_| {
+                error(line, column, format!("environment variable `{}` is not set (referenced by `${{{}}}`)", var, name))
+            });
+        }
+
+        let is_key = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        let Some(path) = split_dotted(name).filter(|_| is_key) else {
+            return Ok(None);
+        };
+        self.lookup(path, name, line, column)
+    }
+
+    /// 查找键的展开值，未知的键返回 None
+    fn lookup(&mut self, path: Vec<String>, name: &str, line: usize, column: usize) -> Result<Option<String>, ParseError> {
+        if let Some(value) = self.resolved.get(&path) {
+            return Ok(Some(value.clone()));
+        }
+        if let Some(start) = self.stack.iter().position(|key| *key == path) {
+            let cycle: Vec<String> = self.stack[start..].iter()
+                .chain([&path])
+                .map(|key| key.join("."))
+                .collect();
+            return Err(error(line, column, format!("reference cycle: {}", cycle.join(" -> "))));
+        }
+
+        let Some(&(value, key_line, key_column)) = self.keys.get(&path) else {
+            return Ok(default_value(&path));
+        };
+        let text = match value {
+            Value::String(text) => {
+                self.stack.push(path.clone());
+                let expanded = self.expand(text, key_line, key_column);
+                self.stack.pop();
+                expanded?
+            },
+            Value::Array(_) => {
+                return Err(error(line, column, format!("`${{{}}}` refers to an array, only single values can be referenced", name)));
+            },
+            scalar => scalar.to_string(),
+        };
+        self.resolved.insert(path, text.clone());
+        Ok(Some(text))
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn error(line: usize, column: usize, message: String) -> ParseError {
+    ParseError { line, column, message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prop::TOP_LEVEL;
+
+    fn expand(content: &str) -> Result<PropDocument, ParseError> {
+        interpolate(&PropDocument::parse(content).unwrap())
+    }
+
+    fn string(doc: &PropDocument, section: &str, key: &str) -> String {
+        doc.get(section, key).and_then(Value::as_str).unwrap().to_string()
+    }
+
+    #[test]
+    fn expands_known_keys() {
+        let doc = expand("id = \"demo\"\nversion = \"1.0\"\nname = \"${id} ${version}\"\n").unwrap();
+        assert_eq!(string(&doc, TOP_LEVEL, "name"), "demo 1.0");
+    }
+
+    #[test]
+    fn expands_nested_and_dotted_keys() {
+        let doc = expand("id = \"demo\"\nzip = \"${github.repo}-${id}.zip\"\n\n[github]\nrepo = \"me/${id}\"\n").unwrap();
+        assert_eq!(string(&doc, TOP_LEVEL, "zip"), "me/demo-demo.zip");
+    }
+
+    #[test]
+    fn expands_environment_variables() {
+        // PATH 在测试环境中总是存在
+        let path = env::var("PATH").unwrap();
+        let doc = expand("dir = \"${env:PATH}\"\n").unwrap();
+        assert_eq!(string(&doc, TOP_LEVEL, "dir"), path);
+        assert!(expand("dir = \"${env:APMM_SURELY_UNSET_VARIABLE}\"\n").is_err());
+    }
+
+    #[test]
+    fn keeps_unknown_names_literal() {
+        let doc = expand("step = \"echo ${HOME} ${0%/*} ${VAR:-x}\"\n").unwrap();
+        assert_eq!(string(&doc, TOP_LEVEL, "step"), "echo ${HOME} ${0%/*} ${VAR:-x}");
+    }
+
+    #[test]
+    fn escapes_dollar_brace() {
+        let doc = expand("id = \"demo\"\nstep = \"echo $${id}\"\n").unwrap();
+        assert_eq!(string(&doc, TOP_LEVEL, "step"), "echo ${id}");
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let error = expand("a = \"${b}\"\nb = \"${a}\"\n").unwrap_err();
+        assert!(error.message.contains("reference cycle"), "{}", error.message);
+    }
+}
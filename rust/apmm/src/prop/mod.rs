@@ -0,0 +1,17 @@
+//! module.prop 文档模型
+//!
+//! module.prop 是 Magisk 的 `key=value` 头部与 TOML 节区的混合格式，
+//! 所有读取和修改都通过 [`PropDocument`] 进行，保证注释和格式在写回时不丢失；
+//! 按结构读取时使用 [`ModuleProp`]，值中的 `${...}` 引用在此时展开，错误带有行列号
+
+mod de;
+mod document;
+mod interpolate;
+mod reflect;
+mod schema;
+mod value;
+
+pub use de::{from_document, from_document_strict};
+pub use document::{split_dotted, PropDocument, TOP_LEVEL};
+pub use schema::{json_schema, Entries, ModuleProp, Workspace};
+pub use value::{ParseError, Value};
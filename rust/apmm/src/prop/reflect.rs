@@ -0,0 +1,232 @@
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::value::ParseError;
+
+/// 追踪时提供给字符串的示例值，同时也是合法的文件权限
+const SAMPLE: &str = "0644";
+
+/// 从 serde 类型推导出的结构
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Shape {
+    #[default]
+    Unknown,
+    String,
+    Integer,
+    Boolean,
+    Array(Box<Shape>),
+    /// 任意键的表，值的结构相同
+    Table(Box<Shape>),
+    /// 固定字段的表
+    Struct(Vec<FieldShape>),
+    /// 由类型自己解释的写法（例如单个路径或路径数组），附带类型给出的说明
+    Custom(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldShape {
+    pub name: &'static str,
+    pub shape: Shape,
+    /// 没有默认值，必须写出
+    pub required: bool,
+}
+
+/// 推导类型 T 的结构
+///
+/// 让 T 的 Deserialize 实现从一个只记录请求的反序列化器读取：
+/// 结构体的字段名来自 serde，必填字段通过逐个省略字段、观察是否报 missing field 得到
+pub fn shape_of<T: DeserializeOwned>() -> Shape {
+    let mut shape = Shape::Unknown;
+    // 追踪器为每种请求都提供合法的示例值，只有自定义类型拒绝示例值时才会失败
+    let _ = T::deserialize(Tracer { shape: &mut shape, path: Vec::new(), omit: None });
+    mark_required::<T>(&mut shape, &mut Vec::new());
+    shape
+}
+
+fn mark_required<T: DeserializeOwned>(shape: &mut Shape, path: &mut Vec<String>) {
+    match shape {
+        Shape::Struct(fields) => {
+            for field in fields {
+                path.push(field.name.to_string());
+                let mut scratch = Shape::Unknown;
+                field.required = T::deserialize(Tracer { shape: &mut scratch, path: Vec::new(), omit: Some(path.as_slice()) }).is_err();
+                mark_required::<T>(&mut field.shape, path);
+                path.pop();
+            }
+        },
+        Shape::Array(item) | Shape::Table(item) => {
+            path.push(ANY_KEY.to_string());
+            mark_required::<T>(item, path);
+            path.pop();
+        },
+        _ => {},
+    }
+}
+
+/// 数组元素和任意键表中的值在路径中的名称
+const ANY_KEY: &str = "*";
+
+struct Tracer<'s, 'o> {
+    shape: &'s mut Shape,
+    /// 当前位置的键路径
+    path: Vec<String>,
+    /// 追踪时要省略的字段路径
+    omit: Option<&'o [String]>,
+}
+
+impl<'o> Tracer<'_, 'o> {
+    fn child<'c>(&self, shape: &'c mut Shape, key: &str) -> Tracer<'c, 'o> {
+        let mut path = self.path.clone();
+        path.push(key.to_string());
+        Tracer { shape, path, omit: self.omit }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_, '_> {
+    type Error = ParseError;
+
+    /// 自定义类型自己决定接受的写法，只记录它的说明
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        *self.shape = Shape::Custom(format!("{}", &visitor as &dyn de::Expected));
+        visitor.visit_str(SAMPLE)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        *self.shape = Shape::Boolean;
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        *self.shape = Shape::Integer;
+        visitor.visit_i64(0)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        *self.shape = Shape::String;
+        visitor.visit_str(SAMPLE)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        let mut item = Shape::Unknown;
+        let result = visitor.visit_seq(SingleElement(Some(self.child(&mut item, ANY_KEY))));
+        *self.shape = Shape::Array(Box::new(item));
+        result
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        let mut value = Shape::Unknown;
+        let result = visitor.visit_map(SingleEntry { value: Some(self.child(&mut value, ANY_KEY)), key_done: false });
+        *self.shape = Shape::Table(Box::new(value));
+        result
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, ParseError> {
+        let mut shapes: Vec<Shape> = vec![Shape::Unknown; fields.len()];
+        let mut access = StructAccess { tracer: &self, fields, shapes: shapes.iter_mut(), index: 0, pending: None };
+        let result = visitor.visit_map(&mut access);
+        *self.shape = Shape::Struct(fields.iter().zip(shapes)
+            .map(|(name, shape)| FieldShape { name, shape, required: false })
+            .collect());
+        result
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i128 u8 u16 u128 f32 f64 char bytes byte_buf unit unit_struct
+        tuple tuple_struct enum identifier
+    }
+}
+
+struct SingleElement<'s, 'o>(Option<Tracer<'s, 'o>>);
+
+impl<'de> SeqAccess<'de> for SingleElement<'_, '_> {
+    type Error = ParseError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ParseError> {
+        match self.0.take() {
+            Some(tracer) => seed.deserialize(tracer).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct SingleEntry<'s, 'o> {
+    value: Option<Tracer<'s, 'o>>,
+    key_done: bool,
+}
+
+impl<'de> MapAccess<'de> for SingleEntry<'_, '_> {
+    type Error = ParseError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, ParseError> {
+        if self.key_done {
+            return Ok(None);
+        }
+        self.key_done = true;
+        seed.deserialize(SAMPLE.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ParseError> {
+        let tracer = self.value.take().expect("next_value_seed called twice");
+        seed.deserialize(tracer)
+    }
+}
+
+struct StructAccess<'t, 's, 'o, 'f> {
+    tracer: &'t Tracer<'s, 'o>,
+    fields: &'static [&'static str],
+    shapes: std::slice::IterMut<'f, Shape>,
+    index: usize,
+    pending: Option<(&'f mut Shape, &'static str)>,
+}
+
+impl<'de> MapAccess<'de> for &mut StructAccess<'_, '_, '_, '_> {
+    type Error = ParseError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, ParseError> {
+        for shape in self.shapes.by_ref() {
+            let name = self.fields[self.index];
+            self.index += 1;
+            let mut path = self.tracer.path.clone();
+            path.push(name.to_string());
+            if self.tracer.omit == Some(path.as_slice()) {
+                continue;
+            }
+            self.pending = Some((shape, name));
+            return seed.deserialize(name.into_deserializer()).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ParseError> {
+        let (shape, name) = self.pending.take().expect("next_value_seed called before next_key_seed");
+        seed.deserialize(self.tracer.child(shape, name))
+    }
+}
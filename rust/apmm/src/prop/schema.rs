@@ -0,0 +1,456 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Map, Value as Json};
+
+use super::reflect::{shape_of, Shape};
+use super::value::ParseError;
+
+/// module.prop 的完整结构
+///
+/// 顶级键对应 Magisk 头部，id / name / version / versionCode 必须存在，author 补上工作区继承的值后必须存在；
+/// 所有节区都拒绝未知的键，拼错的键会直接报错而不是被忽略
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleProp {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(rename = "versionCode")]
+    pub version_code: i64,
+    /// 工作区成员可以省略，从 [workspace] 继承，见 [`ModuleProp::require_author`]
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_license")]
+    pub license: String,
+    #[serde(rename = "updateJson", default)]
+    pub update_json: String,
+    /// [script]：名称 -> 命令
+    #[serde(default)]
+    pub script: Entries<String>,
+    #[serde(default)]
+    pub build: Build,
+    #[serde(default)]
+    pub github: Github,
+    /// 工作区根目录的 module.prop 可以用 [workspace] 代替 apmm-workspace.toml
+    #[serde(default)]
+    pub workspace: Option<Workspace>,
+}
+
+impl ModuleProp {
+    /// 补上工作区继承的值之后仍没有 author 时，与其他必需键一样报缺少
+    pub fn require_author(&self) -> Result<&str, ParseError> {
+        self.author.as_deref().ok_or_else(|| ParseError {
+            line: 1,
+            column: 1,
+            message: "missing required key `author`".to_string(),
+        })
+    }
+}
+
+/// [build]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Build {
+    #[serde(default)]
+    pub module: ModuleFiles,
+    #[serde(default)]
+    pub src: Files,
+    /// [build.variants.<name>]
+    #[serde(default)]
+    pub variants: Entries<Variant>,
+    /// [build.native]：ABI -> 单个路径或路径数组
+    #[serde(default)]
+    pub native: Entries<Paths>,
+    /// [build.vars]：模板变量
+    #[serde(default)]
+    pub vars: Entries<String>,
+    /// [build.inputs]：步骤名 -> 单个 glob 或 glob 数组
+    #[serde(default)]
+    pub inputs: Entries<Paths>,
+    /// [[build.prebuild]] 等数组表，每个键值对是一个步骤
+    #[serde(default)]
+    pub prebuild: Vec<Entries<String>>,
+    #[serde(default)]
+    pub build: Vec<Entries<String>>,
+    #[serde(default)]
+    pub postbuild: Vec<Entries<String>>,
+    #[serde(default)]
+    pub system: System,
+}
+
+/// [build.module]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleFiles {
+    #[serde(default)]
+    pub extra: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// [build.module.modes]：glob -> 权限
+    #[serde(default)]
+    pub modes: Entries<FileMode>,
+}
+
+/// [build.src]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Files {
+    #[serde(default)]
+    pub extra: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// [build.variants.<name>]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Variant {
+    #[serde(default)]
+    pub extra: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// [build.variants.<name>.scripts]：包内路径 -> 项目内源文件
+    #[serde(default)]
+    pub scripts: Entries<String>,
+}
+
+/// [build.system]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct System {
+    #[serde(default)]
+    pub requires: Vec<String>,
+    #[serde(rename = "build-backend", default = "default_build_backend")]
+    pub build_backend: String,
+}
+
+impl Default for System {
+    fn default() -> Self {
+        System { requires: Vec::new(), build_backend: default_build_backend() }
+    }
+}
+
+/// [github]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Github {
+    #[serde(default)]
+    pub repo: String,
+    #[serde(default = "default_branch")]
+    pub branch: String,
+    /// 模块在仓库中的路径，"." 表示仓库根目录
+    #[serde(default = "default_path")]
+    pub path: String,
+    #[serde(rename = "proxy-provider", default)]
+    pub proxy_provider: String,
+}
+
+impl Default for Github {
+    fn default() -> Self {
+        Github {
+            repo: String::new(),
+            branch: default_branch(),
+            path: default_path(),
+            proxy_provider: String::new(),
+        }
+    }
+}
+
+/// [workspace]：成员目录和成员可以继承的默认值
+///
+/// 成员的 module.prop 中写出的键优先，未写出的键才使用这里的值
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// 相对工作区根目录的成员目录，`dir/*` 表示 dir 下所有包含 module.prop 的子目录
+    pub members: Vec<String>,
+    pub author: Option<String>,
+    pub license: Option<String>,
+    #[serde(default)]
+    pub github: WorkspaceGithub,
+    #[serde(default)]
+    pub build: WorkspaceBuild,
+}
+
+/// [workspace.github]，成员的 github.path 由成员目录推导，不继承
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceGithub {
+    pub repo: Option<String>,
+    pub branch: Option<String>,
+    #[serde(rename = "proxy-provider")]
+    pub proxy_provider: Option<String>,
+}
+
+/// [workspace.build]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceBuild {
+    #[serde(default)]
+    pub module: WorkspaceModuleFiles,
+    #[serde(default)]
+    pub prebuild: Vec<Entries<String>>,
+    #[serde(default)]
+    pub build: Vec<Entries<String>>,
+    #[serde(default)]
+    pub postbuild: Vec<Entries<String>>,
+}
+
+/// [workspace.build.module]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceModuleFiles {
+    pub exclude: Option<Vec<String>>,
+}
+
+/// 有默认值的键在未写出时的值，供 `${...}` 引用
+pub(super) fn default_value(path: &[String]) -> Option<String> {
+    match path.join(".").as_str() {
+        "license" => Some(default_license()),
+        "build.system.build-backend" => Some(default_build_backend()),
+        "github.branch" => Some(default_branch()),
+        "github.path" => Some(default_path()),
+        _ => None,
+    }
+}
+
+fn default_license() -> String {
+    "MIT".to_string()
+}
+
+fn default_build_backend() -> String {
+    "apmm".to_string()
+}
+
+fn default_branch() -> String {
+    "main".to_string()
+}
+
+fn default_path() -> String {
+    ".".to_string()
+}
+
+/// 保持声明顺序的键值表（步骤、变体等依赖顺序）
+#[derive(Debug, Clone)]
+pub struct Entries<V>(pub Vec<(String, V)>);
+
+impl<V> Default for Entries<V> {
+    fn default() -> Self {
+        Entries(Vec::new())
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for Entries<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor<V>(PhantomData<V>);
+
+        impl<'de, V: Deserialize<'de>> Visitor<'de> for EntriesVisitor<V> {
+            type Value = Entries<V>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a table")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some((key, value)) = map.next_entry()? {
+                    entries.push((key, value));
+                }
+                Ok(Entries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor(PhantomData))
+    }
+}
+
+/// 单个路径或路径数组
+#[derive(Debug, Clone)]
+pub struct Paths(pub Vec<String>);
+
+impl<'de> Deserialize<'de> for Paths {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PathsVisitor;
+
+        impl<'de> Visitor<'de> for PathsVisitor {
+            type Value = Paths;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a path or an array of paths")
+            }
+
+            fn visit_str<E: de::Error>(self, path: &str) -> Result<Self::Value, E> {
+                Ok(Paths(vec![path.to_string()]))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut paths = Vec::new();
+                while let Some(path) = seq.next_element()? {
+                    paths.push(path);
+                }
+                Ok(Paths(paths))
+            }
+        }
+
+        deserializer.deserialize_any(PathsVisitor)
+    }
+}
+
+/// Unix 权限，写作 "0755" / "755" / "0o600"
+#[derive(Debug, Clone, Copy)]
+pub struct FileMode(pub u32);
+
+impl<'de> Deserialize<'de> for FileMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ModeVisitor;
+
+        impl Visitor<'_> for ModeVisitor {
+            type Value = FileMode;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a file mode like \"0755\"")
+            }
+
+            fn visit_str<E: de::Error>(self, mode: &str) -> Result<Self::Value, E> {
+                crate::build::parse_mode(mode)
+                    .map(FileMode)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Str(mode), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, mode: i64) -> Result<Self::Value, E> {
+                self.visit_str(&mode.to_string())
+            }
+        }
+
+        deserializer.deserialize_any(ModeVisitor)
+    }
+}
+
+/// 编辑器中显示的键说明，键为点分路径（`*` 表示任意名称）
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("id", "Module ID: a letter followed by letters, digits, '.', '_' or '-'"),
+    ("name", "Display name shown by module managers"),
+    ("version", "Version string shown by module managers, e.g. v1.0.0"),
+    ("versionCode", "Integer version used for update checks, higher is newer"),
+    ("author", "Module author, required unless the module is a workspace member that inherits it"),
+    ("description", "One-line description shown by module managers"),
+    ("license", "License identifier, e.g. MIT"),
+    ("updateJson", "URL of the update.json that managers poll for updates"),
+    ("script", "Custom commands: name = \"shell command\""),
+    ("build", "Build configuration"),
+    ("build.module", "Files packed into the module zip"),
+    ("build.module.extra", "Extra files or directories to include"),
+    ("build.module.exclude", "Glob patterns to leave out"),
+    ("build.module.modes", "Unix permissions of zip entries: \"glob\" = \"0755\""),
+    ("build.src", "Files packed into the source tarball (build --sdist)"),
+    ("build.src.extra", "Extra files or directories to include"),
+    ("build.src.exclude", "Glob patterns to leave out"),
+    ("build.variants", "Build variants, one zip each: [build.variants.<name>]"),
+    ("build.variants.*.extra", "Files added on top of [build.module]"),
+    ("build.variants.*.exclude", "Patterns excluded on top of [build.module]"),
+    ("build.variants.*.scripts", "Script overrides: \"path in zip\" = \"project file\""),
+    ("build.native", "Native binaries per ABI: abi = \"path\" or [\"paths\"]"),
+    ("build.vars", "Template variables that replace @NAME@ in scripts"),
+    ("build.inputs", "Files each build step depends on: step = \"glob\" or [\"globs\"]; other steps re-run when any project file changes"),
+    ("build.prebuild", "Steps run before building: name = \"command\""),
+    ("build.build", "Custom build steps, replacing the default packaging when not empty"),
+    ("build.postbuild", "Steps run after building: name = \"command\""),
+    ("build.system", "Build requirements and backend"),
+    ("build.system.requires", "Required tools and versions, e.g. \"apmm>=0.1.0\""),
+    ("build.system.build-backend", "Packaging backend, \"apmm\" for the builtin one"),
+    ("github", "GitHub repository used for releases and updates"),
+    ("github.repo", "Repository as owner/name"),
+    ("github.branch", "Branch used for raw file URLs"),
+    ("github.path", "Module path inside the repository, \".\" for the root"),
+    ("github.proxy-provider", "Proxy prefix for GitHub downloads"),
+    ("workspace", "Workspace of several modules; members inherit the keys they leave out"),
+    ("workspace.members", "Member directories relative to this file, \"dir/*\" for every module under dir"),
+    ("workspace.author", "Default author for members"),
+    ("workspace.license", "Default license for members"),
+    ("workspace.github", "Default [github] for members; github.path defaults to the member directory"),
+    ("workspace.github.repo", "Repository as owner/name"),
+    ("workspace.github.branch", "Branch used for raw file URLs"),
+    ("workspace.github.proxy-provider", "Proxy prefix for GitHub downloads"),
+    ("workspace.build", "Default build settings for members"),
+    ("workspace.build.module.exclude", "Default [build.module] exclude patterns"),
+    ("workspace.build.prebuild", "Default prebuild steps, used when a member defines none"),
+    ("workspace.build.build", "Default build steps, used when a member defines none"),
+    ("workspace.build.postbuild", "Default postbuild steps, used when a member defines none"),
+];
+
+/// 由 [`ModuleProp`] 推导出的 JSON Schema，供编辑器补全和校验
+pub fn json_schema() -> Json {
+    let mut schema = to_json(&shape_of::<ModuleProp>(), &mut Vec::new());
+    if let Json::Object(object) = &mut schema {
+        object.insert("$schema".to_string(), json!("http://json-schema.org/draft-07/schema#"));
+        object.insert("title".to_string(), json!("APMM module.prop"));
+    }
+    schema
+}
+
+fn to_json(shape: &Shape, path: &mut Vec<String>) -> Json {
+    let mut schema = match shape {
+        Shape::Unknown => json!({}),
+        Shape::String => json!({ "type": "string" }),
+        Shape::Integer => json!({ "type": "integer" }),
+        Shape::Boolean => json!({ "type": "boolean" }),
+        Shape::Array(item) => json!({ "type": "array", "items": nested(item, path, "*") }),
+        Shape::Table(value) => json!({ "type": "object", "additionalProperties": nested(value, path, "*") }),
+        Shape::Struct(fields) => {
+            let properties: Map<String, Json> = fields.iter()
+                .map(|field| (field.name.to_string(), nested(&field.shape, path, field.name)))
+                .collect();
+            let required: Vec<&str> = fields.iter().filter(|f| f.required).map(|f| f.name).collect();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": false,
+            })
+        },
+        Shape::Custom(expecting) => json!({ "description": format!("Expects {}", expecting) }),
+    };
+
+    let key = path.join(".");
+    if let Some((_, description)) = DESCRIPTIONS.iter().find(|(name, _)| *name == key)
+        && let Json::Object(object) = &mut schema
+    {
+        object.insert("description".to_string(), json!(description));
+    }
+    schema
+}
+
+fn nested(shape: &Shape, path: &mut Vec<String>, key: &str) -> Json {
+    path.push(key.to_string());
+    let schema = to_json(shape, path);
+    path.pop();
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 仓库中提交的 schema 必须与解析器类型一致，修改类型后用 `apmm schema --output schema/module.prop.schema.json` 重新生成
+    #[test]
+    fn committed_schema_is_up_to_date() {
+        let committed: Json = serde_json::from_str(include_str!("../../schema/module.prop.schema.json")).unwrap();
+        assert_eq!(committed, json_schema());
+    }
+
+    #[test]
+    fn author_is_optional_for_workspace_members() {
+        let required = &json_schema()["required"];
+        assert!(required.as_array().unwrap().iter().all(|name| name != "author"), "{}", required);
+        assert!(required.as_array().unwrap().iter().any(|name| name == "id"), "{}", required);
+
+        let doc = super::super::PropDocument::parse("id=a\nname=A\nversion=1\nversionCode=1\n").unwrap();
+        let prop: ModuleProp = super::super::from_document(&doc).unwrap();
+        assert_eq!(prop.require_author().unwrap_err().to_string(), "1:1: missing required key `author`");
+    }
+}
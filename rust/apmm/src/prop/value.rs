@@ -0,0 +1,393 @@
+use std::fmt;
+
+/// module.prop 中的值
+///
+/// 顶级的 Magisk 写法 `name=My Module` 没有引号，按原文作为字符串；
+/// 整数和布尔值只在能原样写回时才识别（`007` 仍是字符串）
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// 字符串值
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// 解析单独的值文本（例如命令行参数）
+    ///
+    /// 能完整解析为带引号的字符串、数组、整数或布尔值时按对应类型，否则整段作为字符串
+    pub fn parse(text: &str) -> Value {
+        let text = text.trim();
+        match ValueParser::new(text, 1, 1).parse(true) {
+            Ok((value, consumed)) if consumed == text.chars().count() => value,
+            _ => Value::String(text.to_string()),
+        }
+    }
+
+    /// 标量的文本形式，数组返回 None
+    pub fn to_plain_string(&self) -> Option<String> {
+        match self {
+            Value::String(s) => Some(s.clone()),
+            Value::Integer(n) => Some(n.to_string()),
+            Value::Boolean(b) => Some(b.to_string()),
+            Value::Array(_) => None,
+        }
+    }
+
+    /// 以 TOML 语法输出
+    pub fn to_toml(&self) -> String {
+        match self {
+            Value::String(s) => quote(s),
+            Value::Integer(n) => n.to_string(),
+            Value::Boolean(b) => b.to_string(),
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(Value::to_toml).collect();
+                format!("[{}]", items.join(", "))
+            },
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_plain_string() {
+            Some(text) => write!(f, "{}", text),
+            None => write!(f, "{}", self.to_toml()),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<Vec<String>> for Value {
+    fn from(value: Vec<String>) -> Self {
+        Value::Array(value.into_iter().map(Value::String).collect())
+    }
+}
+
+/// 带转义的双引号字符串
+pub fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 可以不加引号写回的顶级字符串（保持 Magisk `key=value` 风格）
+pub fn is_bare_safe(s: &str) -> bool {
+    !s.is_empty()
+        && s.trim() == s
+        && !s.contains(['"', '\'', '#', '\n', '\r', '[', ']'])
+        && scalar(s).as_str().is_some()
+}
+
+/// 解析错误，行列号从 1 开始
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// 值解析器，text 为值开始到逻辑行结束的原文（多行数组包含换行）
+pub struct ValueParser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl ValueParser {
+    pub fn new(text: &str, line: usize, column: usize) -> Self {
+        ValueParser { chars: text.chars().collect(), pos: 0, line, column }
+    }
+
+    /// 解析键值对中的值，返回值及其原文的字符长度
+    ///
+    /// 未加引号的值读到行尾；top_level 为 true 时（Magisk 头部）以 `[` 开头但不是合法数组的值也按字符串处理
+    pub fn parse(mut self, top_level: bool) -> Result<(Value, usize), ParseError> {
+        let value = match self.peek() {
+            Some('"') | Some('\'') => self.string()?,
+            Some('[') if top_level => {
+                let start = (self.pos, self.line, self.column);
+                match self.array() {
+                    Ok(value) => value,
+                    Err(_) => {
+                        (self.pos, self.line, self.column) = start;
+                        self.bare_line()
+                    },
+                }
+            },
+            Some('[') => self.array()?,
+            Some(_) => self.bare_line(),
+            None => return Err(self.error("missing value")),
+        };
+        Ok((value, self.pos))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError { line: self.line, column: self.column, message: message.to_string() }
+    }
+
+    fn string(&mut self) -> Result<Value, ParseError> {
+        let start = self.error("unterminated string");
+        let quote = self.bump().unwrap_or('"');
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(start),
+                Some(c) if c == quote => return Ok(Value::String(value)),
+                Some('\\') if quote == '"' => {
+                    let escape = self.error("invalid escape sequence");
+                    match self.bump() {
+                        Some('"') => value.push('"'),
+                        Some('\\') => value.push('\\'),
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some('r') => value.push('\r'),
+                        Some('u') => {
+                            let hex: String = (0..4).filter_map(|_| self.bump()).collect();
+                            let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or(escape)?;
+                            value.push(c);
+                        },
+                        _ => return Err(escape),
+                    }
+                },
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        let start = self.error("unterminated array");
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace_and_comments();
+            match self.peek() {
+                None => return Err(start),
+                Some(']') => {
+                    self.bump();
+                    return Ok(Value::Array(items));
+                },
+                Some('"') | Some('\'') => items.push(self.string()?),
+                Some('[') => items.push(self.array()?),
+                Some(_) => items.push(self.bare_token()?),
+            }
+            self.skip_whitespace_and_comments();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                },
+                Some(']') => {},
+                None => return Err(start),
+                Some(_) => return Err(self.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == '#' {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// 数组中或需要严格解析处的裸值：整数 / 布尔值
+    fn bare_token(&mut self) -> Result<Value, ParseError> {
+        let start = self.error("invalid value");
+        let mut token = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, ',' | ']' | '#') {
+                break;
+            }
+            token.push(c);
+            self.bump();
+        }
+        match scalar(&token) {
+            Value::String(_) => Err(ParseError { message: format!("invalid value '{}', strings must be quoted here", token), ..start }),
+            value => Ok(value),
+        }
+    }
+
+    /// 行尾的裸值：Magisk 风格的 `key=value`，值到行尾（` #` 之后视为注释）
+    fn bare_line(&mut self) -> Value {
+        let start = self.pos;
+        let mut end = self.pos;
+        while let Some(c) = self.peek() {
+            if c == '\n' || (c == '#' && self.pos > start && self.chars[self.pos - 1].is_whitespace()) {
+                break;
+            }
+            self.bump();
+            // 尾部空白不计入值
+            if !c.is_whitespace() {
+                end = self.pos;
+            }
+        }
+        self.pos = end;
+        let raw: String = self.chars[start..end].iter().collect();
+        scalar(&raw)
+    }
+}
+
+/// 识别裸值的类型
+fn scalar(token: &str) -> Value {
+    match token {
+        "true" => return Value::Boolean(true),
+        "false" => return Value::Boolean(false),
+        _ => {},
+    }
+    if let Ok(n) = token.parse::<i64>()
+        && n.to_string() == token
+    {
+        return Value::Integer(n);
+    }
+    Value::String(token.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, top_level: bool) -> Result<Value, ParseError> {
+        ValueParser::new(text, 3, 7).parse(top_level).map(|(value, _)| value)
+    }
+
+    fn array(items: &[Value]) -> Value {
+        Value::Array(items.to_vec())
+    }
+
+    #[test]
+    fn parses_scalars() {
+        assert_eq!(Value::parse("42"), Value::Integer(42));
+        assert_eq!(Value::parse("-7"), Value::Integer(-7));
+        assert_eq!(Value::parse("007"), Value::from("007"));
+        assert_eq!(Value::parse("true"), Value::Boolean(true));
+        assert_eq!(Value::parse("My Module"), Value::from("My Module"));
+        assert_eq!(Value::parse("\"a\\tb\\u00e9\""), Value::from("a\tbé"));
+        assert_eq!(Value::parse("'C:\\path'"), Value::from("C:\\path"));
+        assert_eq!(Value::parse("[1, \"x\", [true]]"), array(&[1.into(), "x".into(), array(&[true.into()])]));
+    }
+
+    #[test]
+    fn bare_values_stop_at_comments() {
+        assert_eq!(parse("My Module # note", true), Ok(Value::from("My Module")));
+        assert_eq!(parse("a#b", true), Ok(Value::from("a#b")));
+        assert_eq!(parse("[not an array", true), Ok(Value::from("[not an array")));
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let values = [
+            Value::from("plain"),
+            Value::from("quote \" backslash \\ newline \n tab \t"),
+            Value::from("ünïcödé 模块"),
+            Value::from(""),
+            Value::Integer(i64::MIN),
+            Value::Boolean(false),
+            array(&[]),
+            array(&["a".into(), 1.into(), array(&["nested".into()])]),
+        ];
+        for value in values {
+            assert_eq!(Value::parse(&value.to_toml()), value, "{}", value.to_toml());
+        }
+    }
+
+    #[test]
+    fn reports_error_locations() {
+        let error = parse("\"abc", false).unwrap_err();
+        assert_eq!((error.line, error.column, error.message.as_str()), (3, 7, "unterminated string"));
+
+        let error = parse("\"ab\\x\"", false).unwrap_err();
+        assert_eq!((error.line, error.column, error.message.as_str()), (3, 11, "invalid escape sequence"));
+
+        let error = parse("[\n  \"a\"\n  \"b\"]", false).unwrap_err();
+        assert_eq!((error.line, error.column, error.message.as_str()), (5, 3, "expected ',' or ']' in array"));
+
+        let error = parse("[1, two]", false).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 11));
+        assert!(error.message.contains("strings must be quoted"), "{}", error.message);
+
+        let error = parse("[1, 2", false).unwrap_err();
+        assert_eq!((error.line, error.column, error.message.as_str()), (3, 7, "unterminated array"));
+        assert_eq!(error.to_string(), "3:7: unterminated array");
+    }
+
+    #[test]
+    fn bare_safe_strings() {
+        assert!(is_bare_safe("My Module"));
+        assert!(!is_bare_safe("123"));
+        assert!(!is_bare_safe(" padded"));
+        assert!(!is_bare_safe("has # hash"));
+        assert!(!is_bare_safe(""));
+    }
+}
@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::cmds::ApmmConfig;
+
+/// 更新通道文件
+pub const UPDATE_JSON: &str = "update.json";
+/// 更新日志文件
+const CHANGELOG: &str = "CHANGELOG.md";
+
+/// Magisk / KernelSU / APatch 的 updateJson 格式
+#[derive(Debug, Serialize)]
+struct UpdateInfo {
+    version: String,
+    #[serde(rename = "versionCode")]
+    version_code: i64,
+    #[serde(rename = "zipUrl")]
+    zip_url: String,
+    changelog: String,
+}
+
+/// release 命令处理
+pub fn cmd_release(args: &[String]) -> Result<String, String> {
+    if let Some(arg) = args.first() {
+        return Err(format!("Unknown release option: {}", arg));
+    }
+    if !Path::new("module.prop").exists() {
+        return Err("module.prop not found in current directory".to_string());
+    }
+
+    let config = ApmmConfig::load()?;
+    if config.github.repo.is_empty() {
+        return Err("[github] repo is not set in module.prop, cannot derive release URLs".to_string());
+    }
+    println!("🚀 Preparing release {} ({})...", config.version, config.version_code);
+
+    // 更新日志
+    if update_changelog(&config)? {
+        println!("📝 Added {} entry to {}", config.version, CHANGELOG);
+    } else {
+        println!("📝 {} already has an entry for {}", CHANGELOG, config.version);
+    }
+
+    // 更新通道：声明了变体时每个变体一个 update-<variant>.json
+    let variants = &config.build_config.variants;
+    let targets: Vec<(String, String)> = if variants.is_empty() {
+        vec![(UPDATE_JSON.to_string(), format!("{}-{}.zip", config.id, config.version))]
+    } else {
+        variants.iter()
+            .map(|v| (format!("update-{}.json", v.name), format!("{}-{}-{}.zip", config.id, config.version, v.name)))
+            .collect()
+    };
+
+    for (file, zip_name) in &targets {
+        let info = UpdateInfo {
+            version: config.version.clone(),
+            version_code: config.version_code,
+            zip_url: proxied(&config, format!("https://github.com/{}/releases/download/{}/{}", config.github.repo, config.version, zip_name)),
+            changelog: raw_url(&config, CHANGELOG),
+        };
+        let content = serde_json::to_string_pretty(&info)
+            .map_err(|e| format!("Failed to serialize {}: {}", file, e))?;
+        fs::write(file, content + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", file, e))?;
+        println!("📡 Wrote {} -> {}", file, info.zip_url);
+    }
+
+    // 提示 module.prop 中的 updateJson 是否指向生成的文件
+    let expected = raw_url(&config, &targets[0].0);
+    if config.update_json.is_empty() {
+        println!("💡 Set updateJson in module.prop to enable in-app updates:");
+        println!("   updateJson = \"{}\"", expected);
+    } else if variants.is_empty() && config.update_json != expected {
+        println!("⚠️  updateJson in module.prop does not point to the generated file:");
+        println!("   current:  {}", config.update_json);
+        println!("   expected: {}", expected);
+    }
+
+    let success_msg = format!("Release {} prepared", config.version);
+    println!("✅ {}", success_msg);
+    Ok(success_msg)
+}
+
+/// 模块目录下文件的 raw.githubusercontent.com 地址
+pub fn raw_url(config: &ApmmConfig, file: &str) -> String {
+    let path = config.github.path.trim_matches('/').trim_start_matches("./");
+    let prefix = if path.is_empty() || path == "." {
+        String::new()
+    } else {
+        format!("{}/", path)
+    };
+    proxied(config, format!("https://raw.githubusercontent.com/{}/{}/{}{}", config.github.repo, config.github.branch, prefix, file))
+}
+
+/// 设置了 [github] proxy-provider 时在 GitHub 地址前加上代理前缀
+fn proxied(config: &ApmmConfig, url: String) -> String {
+    let proxy = config.github.proxy_provider.trim().trim_end_matches('/');
+    if proxy.is_empty() {
+        url
+    } else {
+        format!("{}/{}", proxy, url)
+    }
+}
+
+/// 在 CHANGELOG.md 顶部添加当前版本的条目，已存在时返回 false
+fn update_changelog(config: &ApmmConfig) -> Result<bool, String> {
+    let existing = fs::read_to_string(CHANGELOG).unwrap_or_default();
+    let heading = format!("## {}", config.version);
+    if existing.lines().any(|line| line == heading || line.starts_with(&format!("{} ", heading))) {
+        return Ok(false);
+    }
+
+    let mut entry = format!("{} ({})\n\n", heading, Utc::now().format("%Y-%m-%d"));
+    let changes = git_changes_since_last_tag();
+    if changes.is_empty() {
+        entry.push_str(&format!("- Release {}\n", config.version));
+    } else {
+        for change in changes {
+            entry.push_str(&format!("- {}\n", change));
+        }
+    }
+
+    fs::write(CHANGELOG, insert_entry(&existing, &entry))
+        .map_err(|e| format!("Failed to write {}: {}", CHANGELOG, e))?;
+    Ok(true)
+}
+
+/// 把条目插入到开头的一级标题（及其后的说明文字）之后、第一个旧条目之前，没有一级标题时补上 `# Changelog`
+fn insert_entry(existing: &str, entry: &str) -> String {
+    let trimmed = existing.trim_start();
+    let (title, body) = match trimmed.split_once('\n') {
+        Some((first, rest)) if first.starts_with("# ") => (first.trim_end(), rest),
+        None if trimmed.starts_with("# ") => (trimmed.trim_end(), ""),
+        _ => ("# Changelog", trimmed),
+    };
+
+    let split = body.match_indices("## ")
+        .map(|(index, _)| index)
+        .find(|&index| index == 0 || body[..index].ends_with('\n'))
+        .unwrap_or(body.len());
+    let intro = body[..split].trim();
+    let entries = body[split..].trim_end();
+
+    let mut content = format!("{}\n\n", title);
+    if !intro.is_empty() {
+        content.push_str(&format!("{}\n\n", intro));
+    }
+    content.push_str(entry);
+    if !entries.is_empty() {
+        content.push_str(&format!("\n{}\n", entries));
+    }
+    content
+}
+
+/// 上一个 tag 之后的提交标题（不是 Git 仓库时返回空）
+fn git_changes_since_last_tag() -> Vec<String> {
+    let last_tag = Command::new("git")
+        .args(["describe", "--tags", "--abbrev=0"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+
+    let range = match &last_tag {
+        Some(tag) => format!("{}..HEAD", tag),
+        None => "HEAD".to_string(),
+    };
+    Command::new("git")
+        .args(["log", "--no-merges", "--pretty=format:%s", "-n", "50", &range])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRY: &str = "## v2 (2026-01-02)\n\n- New\n";
+
+    fn config(github: &str) -> ApmmConfig {
+        let content = format!("id=demo\nname=Demo\nversion=v1\nversionCode=1\nauthor=me\n\n[github]\nrepo = \"me/demo\"\n{}", github);
+        let doc = crate::prop::PropDocument::parse(&content).unwrap();
+        ApmmConfig::from_document(&doc, Path::new("module.prop")).unwrap()
+    }
+
+    #[test]
+    fn raw_urls_follow_the_module_path() {
+        assert_eq!(raw_url(&config(""), "update.json"), "https://raw.githubusercontent.com/me/demo/main/update.json");
+        assert_eq!(
+            raw_url(&config("path = \"./modules/demo/\"\nbranch = \"dev\"\n"), "update.json"),
+            "https://raw.githubusercontent.com/me/demo/dev/modules/demo/update.json"
+        );
+    }
+
+    #[test]
+    fn proxy_provider_prefixes_github_urls() {
+        let config = config("proxy-provider = \"https://proxy.example/github/\"\n");
+        assert_eq!(
+            raw_url(&config, "CHANGELOG.md"),
+            "https://proxy.example/github/https://raw.githubusercontent.com/me/demo/main/CHANGELOG.md"
+        );
+    }
+
+    #[test]
+    fn creates_changelog() {
+        assert_eq!(insert_entry("", ENTRY), format!("# Changelog\n\n{}", ENTRY));
+    }
+
+    #[test]
+    fn keeps_custom_title_and_intro() {
+        let existing = "# Release notes\n\nAll notable changes.\n\n## v1 (2026-01-01)\n\n- Old\n";
+        assert_eq!(
+            insert_entry(existing, ENTRY),
+            format!("# Release notes\n\nAll notable changes.\n\n{}\n## v1 (2026-01-01)\n\n- Old\n", ENTRY)
+        );
+    }
+
+    #[test]
+    fn inserts_below_title_only() {
+        assert_eq!(insert_entry("# Changes\n", ENTRY), format!("# Changes\n\n{}", ENTRY));
+        assert_eq!(
+            insert_entry("# Changelog\n## v1\n- Old\n", ENTRY),
+            format!("# Changelog\n\n{}\n## v1\n- Old\n", ENTRY)
+        );
+    }
+
+    #[test]
+    fn adds_title_when_missing() {
+        assert_eq!(
+            insert_entry("## v1\n\n- Old\n", ENTRY),
+            format!("# Changelog\n\n{}\n## v1\n\n- Old\n", ENTRY)
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use crate::prop::json_schema;
+
+/// 项目内的 schema 文件（.apmm/ 不会被打包）
+const SCHEMA_FILE: &str = ".apmm/module.prop.schema.json";
+/// Taplo / Even Better TOML 的配置文件
+const TAPLO_CONFIG: &str = ".taplo.toml";
+/// VS Code 工作区设置
+const VSCODE_SETTINGS: &str = ".vscode/settings.json";
+
+/// schema 命令处理
+///
+/// 无参数时输出 module.prop 的 JSON Schema；`--output <file>` 写入文件；
+/// `--editor` 在当前项目中写入 schema 并配置 Taplo 关联，让编辑器提供补全和校验
+pub fn cmd_schema(args: &[String]) -> Result<String, String> {
+    let content = serde_json::to_string_pretty(&json_schema())
+        .map_err(|e| format!("Failed to serialize schema: {}", e))? + "\n";
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] => {
+            print!("{}", content);
+            Ok("Schema printed".to_string())
+        },
+        ["--output", file] => {
+            write_file(Path::new(file), &content)?;
+            let success_msg = format!("Schema written to {}", file);
+            println!("✅ {}", success_msg);
+            Ok(success_msg)
+        },
+        ["--editor"] => setup_editor(&content),
+        _ => Err("Usage: apmm schema [--output <file> | --editor]".to_string()),
+    }
+}
+
+/// 写入 schema 并把 module.prop 关联到它
+fn setup_editor(content: &str) -> Result<String, String> {
+    if !Path::new("module.prop").exists() {
+        return Err("module.prop not found in current directory".to_string());
+    }
+
+    write_file(Path::new(SCHEMA_FILE), content)?;
+    println!("📄 Wrote {}", SCHEMA_FILE);
+
+    let rule = format!(
+        "[[rule]]\ninclude = [\"module.prop\"]\n\n[rule.schema]\npath = \"{}\"\n",
+        SCHEMA_FILE
+    );
+    let taplo = Path::new(TAPLO_CONFIG);
+    if !taplo.exists() {
+        let config = format!(
+            "# module.prop is checked against the APMM schema (written by apmm schema --editor)\ninclude = [\"module.prop\", \"**/*.toml\"]\n\n{}",
+            rule
+        );
+        write_file(taplo, &config)?;
+        println!("📄 Wrote {}", TAPLO_CONFIG);
+    } else {
+        let existing = fs::read_to_string(taplo)
+            .map_err(|e| format!("Failed to read {}: {}", TAPLO_CONFIG, e))?;
+        if existing.contains(SCHEMA_FILE) {
+            println!("📄 {} already uses the schema", TAPLO_CONFIG);
+        } else {
+            let separator = if existing.ends_with('\n') { "\n" } else { "\n\n" };
+            write_file(taplo, &format!("{}{}{}", existing, separator, rule))?;
+            println!("📄 Added a module.prop rule to {}", TAPLO_CONFIG);
+            println!("   Make sure its top-level include also lists \"module.prop\"");
+        }
+    }
+
+    // module.prop 没有 .toml 扩展名，VS Code 需要文件关联才会交给 Even Better TOML
+    let settings = Path::new(VSCODE_SETTINGS);
+    if !settings.exists() {
+        write_file(settings, "{\n    \"files.associations\": {\n        \"module.prop\": \"toml\"\n    }\n}\n")?;
+        println!("📄 Wrote {}", VSCODE_SETTINGS);
+    } else if !fs::read_to_string(settings).unwrap_or_default().contains("module.prop") {
+        println!("💡 Add \"files.associations\": {{ \"module.prop\": \"toml\" }} to {}", VSCODE_SETTINGS);
+    }
+
+    let success_msg = "Editor schema support configured for module.prop".to_string();
+    println!("✅ {}", success_msg);
+    Ok(success_msg)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    fs::write(path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
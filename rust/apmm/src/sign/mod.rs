@@ -0,0 +1,432 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::build::{hash_file, BUILD_DIR, DIST_DIR};
+
+/// 签名算法
+const ALGORITHM: &str = "ed25519";
+/// 签名文件后缀：<artifact>.sig
+const SIG_EXT: &str = "sig";
+/// 产物清单文件
+const MANIFEST_FILE: &str = "SHA256SUMS";
+/// 默认密钥名
+const DEFAULT_KEY: &str = "default";
+
+/// 分离式签名文件内容
+#[derive(Debug, Serialize, Deserialize)]
+struct DetachedSignature {
+    algorithm: String,
+    #[serde(rename = "public-key")]
+    public_key: String,
+    sha256: String,
+    signature: String,
+}
+
+/// sign 命令处理
+pub fn cmd_sign(args: &[String]) -> Result<String, String> {
+    let (key_name, files) = parse_args(args)?;
+    let signing_key = load_or_create_key(&keys_dir()?, &key_name)?;
+    let public_key = to_hex(signing_key.verifying_key().as_bytes());
+
+    let artifacts = if files.is_empty() { dist_artifacts()? } else { files };
+    if artifacts.is_empty() {
+        return Err("No artifacts found in build/dist/, run 'apmm build' first".to_string());
+    }
+
+    println!("🔏 Signing artifacts with key '{}' ({})", key_name, &public_key[..16]);
+    sign_artifacts(&signing_key, &artifacts)?;
+
+    let success_msg = format!("Signed {} artifacts", artifacts.len());
+    println!("✅ {}", success_msg);
+    Ok(success_msg)
+}
+
+/// 签名每个产物，并在同目录生成签名过的 SHA256SUMS
+fn sign_artifacts(signing_key: &SigningKey, artifacts: &[PathBuf]) -> Result<(), String> {
+    let mut manifest = String::new();
+    for artifact in artifacts {
+        let sha256 = sign_file(signing_key, artifact)?;
+        let name = file_name(artifact)?;
+        manifest.push_str(&format!("{}  {}\n", sha256, name));
+        println!("   ✓ {}", name);
+    }
+
+    // 清单与产物放在同一目录，本身也会被签名
+    let manifest_dir = artifacts[0].parent().map(Path::to_path_buf).unwrap_or_default();
+    let manifest_path = manifest_dir.join(MANIFEST_FILE);
+    fs::write(&manifest_path, &manifest)
+        .map_err(|e| format!("Failed to write {}: {}", manifest_path.display(), e))?;
+    sign_file(signing_key, &manifest_path)?;
+    println!("   ✓ {}", MANIFEST_FILE);
+    Ok(())
+}
+
+/// verify 命令处理
+pub fn cmd_verify(args: &[String]) -> Result<String, String> {
+    let (_, files) = parse_args(args)?;
+    let artifacts = if files.is_empty() { dist_artifacts()? } else { files };
+    if artifacts.is_empty() {
+        return Err("No artifacts found to verify".to_string());
+    }
+
+    println!("🔍 Verifying artifacts...");
+    verify_artifacts(&artifacts, &trusted_keys(&keys_dir()?))?;
+
+    let success_msg = format!("Verified {} artifacts", artifacts.len());
+    println!("✅ {}", success_msg);
+    Ok(success_msg)
+}
+
+/// 校验每个产物的签名，同目录存在清单时一并校验
+fn verify_artifacts(artifacts: &[PathBuf], trusted: &[String]) -> Result<(), String> {
+    for artifact in artifacts {
+        verify_signature(artifact, trusted)?;
+        println!("   ✓ {}", file_name(artifact)?);
+    }
+
+    let manifest_dir = artifacts[0].parent().map(Path::to_path_buf).unwrap_or_default();
+    let manifest_path = manifest_dir.join(MANIFEST_FILE);
+    if manifest_path.exists() {
+        verify_manifest(&manifest_path, trusted)?;
+        println!("   ✓ {}", MANIFEST_FILE);
+    }
+    Ok(())
+}
+
+/// 校验产物的分离式签名：签名存在、内容未被篡改、公钥受信任
+pub fn verify_artifact(artifact: &Path) -> Result<(), String> {
+    verify_signature(artifact, &trusted_keys(&keys_dir()?))
+}
+
+fn verify_signature(artifact: &Path, trusted: &[String]) -> Result<(), String> {
+    let sig_path = signature_path(artifact);
+    let content = fs::read_to_string(&sig_path)
+        .map_err(|_| format!("{} is not signed (missing {})", artifact.display(), sig_path.display()))?;
+    let detached: DetachedSignature = toml::from_str(&content)
+        .map_err(|e| format!("Invalid signature file {}: {}", sig_path.display(), e))?;
+
+    if detached.algorithm != ALGORITHM {
+        return Err(format!("Unsupported signature algorithm '{}' in {}", detached.algorithm, sig_path.display()));
+    }
+
+    let sha256 = hash_file(artifact)?;
+    if sha256 != detached.sha256 {
+        return Err(format!("{} has been modified: SHA-256 does not match its signature", artifact.display()));
+    }
+
+    let public_key = normalize_key(&detached.public_key);
+    let verifying_key = parse_public_key(&public_key)?;
+    if !trusted.contains(&public_key) {
+        return Err(format!("{} is signed by an untrusted key {}", artifact.display(), public_key));
+    }
+
+    let signature_bytes: [u8; 64] = from_hex(&detached.signature)?
+        .try_into()
+        .map_err(|_| format!("Invalid signature length in {}", sig_path.display()))?;
+    verifying_key
+        .verify(signed_message(&sha256).as_bytes(), &Signature::from_bytes(&signature_bytes))
+        .map_err(|_| format!("Invalid signature for {}", artifact.display()))
+}
+
+/// 校验清单签名以及其中列出的每个文件的哈希
+fn verify_manifest(manifest_path: &Path, trusted: &[String]) -> Result<(), String> {
+    verify_signature(manifest_path, trusted)?;
+
+    let dir = manifest_path.parent().unwrap_or(Path::new("."));
+    let content = fs::read_to_string(manifest_path)
+        .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let (expected, name) = line.split_once("  ")
+            .ok_or_else(|| format!("Invalid line in {}: {}", MANIFEST_FILE, line))?;
+        let path = dir.join(name);
+        if !path.exists() {
+            return Err(format!("{} lists missing file {}", MANIFEST_FILE, name));
+        }
+        if hash_file(&path)? != expected {
+            return Err(format!("{} does not match {}", name, MANIFEST_FILE));
+        }
+    }
+    Ok(())
+}
+
+/// 为文件生成 <file>.sig，返回文件的 SHA-256
+fn sign_file(signing_key: &SigningKey, path: &Path) -> Result<String, String> {
+    let sha256 = hash_file(path)?;
+    let signature = signing_key.sign(signed_message(&sha256).as_bytes());
+    let detached = DetachedSignature {
+        algorithm: ALGORITHM.to_string(),
+        public_key: to_hex(signing_key.verifying_key().as_bytes()),
+        sha256: sha256.clone(),
+        signature: to_hex(&signature.to_bytes()),
+    };
+
+    let sig_path = signature_path(path);
+    let content = toml::to_string(&detached)
+        .map_err(|e| format!("Failed to serialize signature: {}", e))?;
+    fs::write(&sig_path, content)
+        .map_err(|e| format!("Failed to write {}: {}", sig_path.display(), e))?;
+    Ok(sha256)
+}
+
+/// 实际被签名的消息（带版本前缀，避免与其他用途的签名混淆）
+fn signed_message(sha256: &str) -> String {
+    format!("apmm-signature-v1:{}", sha256)
+}
+
+fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(SIG_EXT);
+    PathBuf::from(name)
+}
+
+/// 解析 [--key <name>] [files...]
+fn parse_args(args: &[String]) -> Result<(String, Vec<PathBuf>), String> {
+    let mut key_name = DEFAULT_KEY.to_string();
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--key" => {
+                key_name = args.next().ok_or("--key requires a key name")?.clone();
+            },
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    Ok((key_name, files))
+}
+
+/// build/dist/ 下所有待签名的产物（不含签名文件和清单）
+fn dist_artifacts() -> Result<Vec<PathBuf>, String> {
+    let dist_dir = Path::new(BUILD_DIR).join(DIST_DIR);
+    if !dist_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut artifacts = Vec::new();
+    let entries = fs::read_dir(&dist_dir)
+        .map_err(|e| format!("Failed to read {}: {}", dist_dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_file() && !name.ends_with(&format!(".{}", SIG_EXT)) && name != MANIFEST_FILE {
+            artifacts.push(path);
+        }
+    }
+    artifacts.sort();
+    Ok(artifacts)
+}
+
+fn file_name(path: &Path) -> Result<String, String> {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid artifact path: {}", path.display()))
+}
+
+/// APMM 元数据目录（meta.toml 所在目录），默认 ~/data/adb/.apmm/，可通过 APMM_ROOT 覆盖
+fn meta_dir() -> Result<PathBuf, String> {
+    if let Ok(root) = env::var("APMM_ROOT") {
+        return Ok(PathBuf::from(root));
+    }
+    env::home_dir()
+        .map(|home| home.join("data").join("adb").join(".apmm"))
+        .ok_or_else(|| "Failed to determine home directory".to_string())
+}
+
+/// 密钥目录：<meta>/keys/，<name>.key 为私钥，*.pub 为受信任的公钥
+fn keys_dir() -> Result<PathBuf, String> {
+    Ok(meta_dir()?.join("keys"))
+}
+
+/// 加载密钥目录中的签名密钥，不存在时生成新的密钥对
+fn load_or_create_key(dir: &Path, name: &str) -> Result<SigningKey, String> {
+    let key_path = dir.join(format!("{}.key", name));
+
+    if key_path.exists() {
+        let content = fs::read_to_string(&key_path)
+            .map_err(|e| format!("Failed to read {}: {}", key_path.display(), e))?;
+        let bytes: [u8; 32] = from_hex(content.trim())?
+            .try_into()
+            .map_err(|_| format!("Invalid key file {}", key_path.display()))?;
+        return Ok(SigningKey::from_bytes(&bytes));
+    }
+
+    let mut secret = [0u8; 32];
+    getrandom::fill(&mut secret)
+        .map_err(|e| format!("Failed to generate key: {}", e))?;
+    let signing_key = SigningKey::from_bytes(&secret);
+
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    // 创建时就只允许所有者读写，避免私钥在写入期间被其他用户读取
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(&key_path)
+        .and_then(|mut file| file.write_all(to_hex(&secret).as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", key_path.display(), e))?;
+
+    let pub_path = dir.join(format!("{}.pub", name));
+    fs::write(&pub_path, to_hex(signing_key.verifying_key().as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", pub_path.display(), e))?;
+
+    println!("🔑 Generated new signing key '{}'", name);
+    println!("   Private key: {}", key_path.display());
+    println!("   Public key: {}", pub_path.display());
+    Ok(signing_key)
+}
+
+/// 受信任的公钥：密钥目录下所有 *.pub 文件
+fn trusted_keys(dir: &Path) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "pub")
+                && let Ok(content) = fs::read_to_string(&path)
+            {
+                keys.push(normalize_key(&content));
+            }
+        }
+    }
+    keys
+}
+
+/// 公钥统一为去掉空白的小写十六进制
+fn normalize_key(hex: &str) -> String {
+    hex.trim().to_lowercase()
+}
+
+fn parse_public_key(hex: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = from_hex(hex)?
+        .try_into()
+        .map_err(|_| "Invalid public key length".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid public key: {}", e))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return Err("Invalid hex string".to_string());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| "Invalid hex string".to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 临时目录：keys/ 为受信任的密钥目录，dist/ 下有两个产物
+    fn setup(name: &str) -> (PathBuf, PathBuf, Vec<PathBuf>) {
+        let root = env::temp_dir().join(format!("apmm-sign-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let dist = root.join("dist");
+        fs::create_dir_all(&dist).unwrap();
+        let artifacts = vec![dist.join("demo-v1.zip"), dist.join("demo-v1-src.tar.gz")];
+        for artifact in &artifacts {
+            fs::write(artifact, format!("content of {}", artifact.display())).unwrap();
+        }
+        (root.join("keys"), root, artifacts)
+    }
+
+    fn signed(name: &str) -> (PathBuf, Vec<PathBuf>, Vec<String>) {
+        let (keys, root, artifacts) = setup(name);
+        let signing_key = load_or_create_key(&keys, "test").unwrap();
+        sign_artifacts(&signing_key, &artifacts).unwrap();
+        (root, artifacts, trusted_keys(&keys))
+    }
+
+    #[test]
+    fn sign_then_verify() {
+        let (keys, root, artifacts) = setup("round-trip");
+        let signing_key = load_or_create_key(&keys, "test").unwrap();
+        sign_artifacts(&signing_key, &artifacts).unwrap();
+
+        let trusted = trusted_keys(&keys);
+        assert_eq!(trusted, vec![to_hex(signing_key.verifying_key().as_bytes())]);
+        assert_eq!(verify_artifacts(&artifacts, &trusted), Ok(()));
+        assert!(signature_path(&root.join("dist").join(MANIFEST_FILE)).exists());
+
+        // 再次加载得到同一个密钥，私钥只有所有者可读写
+        let reloaded = load_or_create_key(&keys, "test").unwrap();
+        assert_eq!(reloaded.to_bytes(), signing_key.to_bytes());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(keys.join("test.key")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rejects_tampered_artifacts() {
+        let (root, artifacts, trusted) = signed("tampered");
+        fs::write(&artifacts[0], "injected payload").unwrap();
+        let error = verify_signature(&artifacts[0], &trusted).unwrap_err();
+        assert!(error.contains("has been modified"), "{}", error);
+
+        fs::remove_file(signature_path(&artifacts[1])).unwrap();
+        let error = verify_signature(&artifacts[1], &trusted).unwrap_err();
+        assert!(error.contains("is not signed"), "{}", error);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rejects_untrusted_or_forged_signatures() {
+        let (root, artifacts, trusted) = signed("untrusted");
+
+        // 其他密钥签名的产物
+        let other = load_or_create_key(&root.join("other-keys"), "other").unwrap();
+        sign_file(&other, &artifacts[0]).unwrap();
+        let error = verify_signature(&artifacts[0], &trusted).unwrap_err();
+        assert!(error.contains("untrusted key"), "{}", error);
+
+        // 受信任的公钥配上伪造的签名
+        let sig_path = signature_path(&artifacts[1]);
+        let mut detached: DetachedSignature = toml::from_str(&fs::read_to_string(&sig_path).unwrap()).unwrap();
+        detached.public_key = detached.public_key.to_uppercase();
+        detached.signature = to_hex(&other.sign(signed_message(&detached.sha256).as_bytes()).to_bytes());
+        fs::write(&sig_path, toml::to_string(&detached).unwrap()).unwrap();
+        let error = verify_signature(&artifacts[1], &trusted).unwrap_err();
+        assert!(error.starts_with("Invalid signature for"), "{}", error);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rejects_mismatched_manifest() {
+        let (root, artifacts, trusted) = signed("manifest");
+        let manifest_path = root.join("dist").join(MANIFEST_FILE);
+
+        // 产物被替换并单独重新签名，清单中的哈希不再一致
+        fs::write(&artifacts[0], "replaced").unwrap();
+        let signing_key = load_or_create_key(&root.join("keys"), "test").unwrap();
+        sign_file(&signing_key, &artifacts[0]).unwrap();
+        assert_eq!(verify_signature(&artifacts[0], &trusted), Ok(()));
+        let error = verify_artifacts(&artifacts, &trusted).unwrap_err();
+        assert_eq!(error, "demo-v1.zip does not match SHA256SUMS");
+
+        // 清单本身被改动
+        let content = fs::read_to_string(&manifest_path).unwrap();
+        fs::write(&manifest_path, content.replace("demo-v1.zip", "other.zip")).unwrap();
+        let error = verify_manifest(&manifest_path, &trusted).unwrap_err();
+        assert!(error.contains("has been modified"), "{}", error);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
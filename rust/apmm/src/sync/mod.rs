@@ -1,5 +1,6 @@
 use crate::env::ApmmMeta;
-use chrono::{Utc, Datelike, Timelike};
+use crate::init::generate_version_code;
+use crate::prop::{PropDocument, Value, TOP_LEVEL};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -220,63 +221,37 @@ fn sync_current_project_with_upgrade() -> Result<String, String> {
     Ok(format!("Project '{}' synchronized and version upgraded", module_id))
 }
 
-/// 从module.prop内容中提取模块ID
+/// 从module.prop内容中提取模块ID（同时支持 `id = "x"` 和 Magisk 的 `id=x`）
 fn extract_module_id(content: &str) -> Result<String, String> {
-    for line in content.lines() {
-        let line = line.trim();
-        if line.starts_with("id = ") {
-            let id = line.strip_prefix("id = ")
-                .ok_or("Invalid id format")?
-                .trim_matches('"')
-                .trim();
-            return Ok(id.to_string());
-        }
-    }
-    Err("Module ID not found in module.prop".to_string())
+    let doc = PropDocument::parse(content)
+        .map_err(|e| format!("module.prop:{}", e))?;
+    doc.get(TOP_LEVEL, "id")
+        .and_then(Value::to_plain_string)
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| "Module ID not found in module.prop".to_string())
 }
 
-/// 升级项目版本
+/// 升级项目版本（只修改 version / versionCode 的值，其余内容和格式保持不变）
 fn upgrade_project_version(module_id: &str) -> Result<(), String> {
     println!("🔄 Upgrading version for project '{}'...", module_id);
 
-    // 读取当前module.prop
-    let content = fs::read_to_string("module.prop")
-        .map_err(|e| format!("Failed to read module.prop: {}", e))?;
+    let path = Path::new("module.prop");
+    let mut doc = PropDocument::load(path)?;
 
-    let mut new_content = String::new();
-    let mut version_upgraded = false;
-    let mut version_code_upgraded = false;
+    if let Some(current_version) = doc.get(TOP_LEVEL, "version").and_then(Value::to_plain_string) {
+        let new_version = upgrade_version_string(&current_version)?;
+        println!("   Version: {} -> {}", current_version, new_version);
+        doc.set(TOP_LEVEL, "version", Value::from(new_version));
+    }
 
-    for line in content.lines() {
-        let line = line.trim();
-        
-        if line.starts_with("version = ") && !version_upgraded {
-            // 提取当前版本
-            if let Some(version_part) = line.strip_prefix("version = ") {
-                let current_version = version_part.trim_matches('"').trim();
-                let new_version = upgrade_version_string(current_version)?;
-                new_content.push_str(&format!("version = \"{}\"\n", new_version));
-                version_upgraded = true;
-                println!("   Version: {} -> {}", current_version, new_version);
-            } else {
-                new_content.push_str(line);
-                new_content.push('\n');
-            }
-        } else if line.starts_with("versionCode = ") && !version_code_upgraded {
-            // 生成新的版本代码
-            let new_version_code = generate_version_code();
-            new_content.push_str(&format!("versionCode = {}\n", new_version_code));
-            version_code_upgraded = true;
-            println!("   Version Code: {}", new_version_code);
-        } else {
-            new_content.push_str(line);
-            new_content.push('\n');
-        }
+    if doc.get(TOP_LEVEL, "versionCode").is_some() {
+        let new_version_code = generate_version_code();
+        doc.set(TOP_LEVEL, "versionCode", Value::from(new_version_code));
+        println!("   Version Code: {}", new_version_code);
     }
 
-    // 写回文件
-    fs::write("module.prop", new_content)
-        .map_err(|e| format!("Failed to write updated module.prop: {}", e))?;
+    doc.save(path)?;
 
     println!("✅ Version upgraded successfully");
     Ok(())
@@ -331,15 +306,6 @@ fn get_git_patch_info() -> Option<u32> {
     }
 }
 
-/// 生成版本代码
-fn generate_version_code() -> i64 {
-    use chrono::Utc;
-    let now = Utc::now();
-    let version_code = format!("{:04}{:02}{:02}{:02}{:02}", 
-        now.year(), now.month(), now.day(), now.hour(), now.minute());
-    version_code.parse().unwrap_or(2025061700)
-}
-
 /// 验证项目有效性
 fn validate_project(module_id: &str) -> Result<(), String> {
     println!("🔍 Validating project '{}'...", module_id);
@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::cmds::{BuildConfig, BuildStep};
+use crate::prop::{self, PropDocument, Value, Workspace, TOP_LEVEL};
+
+/// 工作区配置文件
+pub const WORKSPACE_FILE: &str = "apmm-workspace.toml";
+
+/// apmm-workspace.toml 只包含 [workspace]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkspaceFile {
+    workspace: Workspace,
+}
+
+/// 工作区根目录的 module.prop 中只关心 [workspace]，其余内容由模块自己校验
+#[derive(Deserialize)]
+struct WorkspaceSection {
+    workspace: Option<Workspace>,
+}
+
+/// 多个模块项目共享的工作区
+#[derive(Debug, Clone)]
+pub struct ApmmWorkspace {
+    /// apmm-workspace.toml 或带 [workspace] 的 module.prop 所在目录
+    pub root: PathBuf,
+    config: Workspace,
+}
+
+impl ApmmWorkspace {
+    /// 从 start 开始向上查找最近的工作区定义
+    pub fn find(start: &Path) -> Result<Option<Self>, String> {
+        let start = start.canonicalize()
+            .map_err(|e| format!("Failed to resolve {}: {}", start.display(), e))?;
+
+        for dir in start.ancestors() {
+            let workspace_file = dir.join(WORKSPACE_FILE);
+            if workspace_file.exists() {
+                let doc = PropDocument::load(&workspace_file)?;
+                let file: WorkspaceFile = prop::from_document(&doc)
+                    .map_err(|e| format!("{}:{}", workspace_file.display(), e))?;
+                return Ok(Some(ApmmWorkspace { root: dir.to_path_buf(), config: file.workspace }));
+            }
+
+            // 没有 [workspace] 或无法解析的 module.prop 属于它自己的项目，这里不报错
+            let module_prop = dir.join("module.prop");
+            let Some(doc) = fs::read_to_string(&module_prop).ok()
+                .and_then(|content| PropDocument::parse(&content).ok())
+                .filter(|doc| doc.has_section("workspace"))
+            else {
+                continue;
+            };
+            let section: WorkspaceSection = prop::from_document(&doc)
+                .map_err(|e| format!("{}:{}", module_prop.display(), e))?;
+            if let Some(config) = section.workspace {
+                return Ok(Some(ApmmWorkspace { root: dir.to_path_buf(), config }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 查找 project_dir 所属的工作区，最近的工作区不包含该项目时返回 None
+    pub fn for_member(project_dir: &Path) -> Result<Option<Self>, String> {
+        let Some(workspace) = Self::find(project_dir)? else {
+            return Ok(None);
+        };
+        let project_dir = project_dir.canonicalize()
+            .map_err(|e| format!("Failed to resolve {}: {}", project_dir.display(), e))?;
+        let is_member = workspace.members()?.contains(&project_dir);
+        Ok(is_member.then_some(workspace))
+    }
+
+    /// 按声明顺序展开成员目录
+    pub fn members(&self) -> Result<Vec<PathBuf>, String> {
+        let mut members = Vec::new();
+        for pattern in &self.config.members {
+            let dirs = match pattern.strip_suffix("/*") {
+                Some(parent) => {
+                    let parent = self.root.join(parent);
+                    let entries = fs::read_dir(&parent)
+                        .map_err(|e| format!("Failed to read workspace members in {}: {}", parent.display(), e))?;
+                    let mut dirs: Vec<PathBuf> = entries
+                        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                        .filter(|path| path.join("module.prop").is_file())
+                        .collect();
+                    dirs.sort();
+                    dirs
+                },
+                None => vec![self.root.join(pattern)],
+            };
+
+            for dir in dirs {
+                if !dir.join("module.prop").is_file() {
+                    return Err(format!("Workspace member {} has no module.prop", dir.display()));
+                }
+                let dir = dir.canonicalize()
+                    .map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))?;
+                if !members.contains(&dir) {
+                    members.push(dir);
+                }
+            }
+        }
+        Ok(members)
+    }
+
+    /// 成员相对工作区根目录的路径，使用 `/` 分隔，根目录本身为 "."
+    pub fn relative(&self, member: &Path) -> String {
+        let relative: Vec<String> = member.strip_prefix(&self.root)
+            .unwrap_or(member)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if relative.is_empty() { ".".to_string() } else { relative.join("/") }
+    }
+
+    /// 把成员 module.prop 中没有写出的键补上工作区的默认值（只修改内存中的文档）
+    pub fn inherit(&self, doc: &mut PropDocument, member: &Path) {
+        let mut inherit = |section: &str, key: &str, value: Option<Value>| {
+            if let Some(value) = value
+                && doc.get(section, key).is_none()
+            {
+                doc.set(section, key, value);
+            }
+        };
+        let string = |value: &Option<String>| value.clone().map(Value::String);
+
+        let config = &self.config;
+        inherit(TOP_LEVEL, "author", string(&config.author));
+        inherit(TOP_LEVEL, "license", string(&config.license));
+        inherit("github", "repo", string(&config.github.repo));
+        inherit("github", "branch", string(&config.github.branch));
+        inherit("github", "proxy-provider", string(&config.github.proxy_provider));
+        inherit("build.module", "exclude", config.build.module.exclude.clone()
+            .map(|patterns| Value::Array(patterns.into_iter().map(Value::String).collect())));
+
+        // 工作区通常就是仓库根目录，成员在仓库中的路径就是它在工作区中的路径
+        if doc.get("github", "repo").is_some() {
+            let path = member.canonicalize().map(|member| self.relative(&member));
+            if let Ok(path) = path
+                && doc.get("github", "path").is_none()
+            {
+                doc.set("github", "path", Value::String(path));
+            }
+        }
+    }
+
+    /// 成员没有定义的构建步骤使用工作区的步骤
+    pub fn inherit_steps(&self, build_config: &mut BuildConfig) {
+        let build = &self.config.build;
+        for (steps, defaults) in [
+            (&mut build_config.prebuild_steps, &build.prebuild),
+            (&mut build_config.build_steps, &build.build),
+            (&mut build_config.postbuild_steps, &build.postbuild),
+        ] {
+            if steps.is_empty() {
+                *steps = BuildStep::from_tables(defaults.clone());
+            }
+        }
+    }
+}